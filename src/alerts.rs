@@ -0,0 +1,578 @@
+use crate::{
+    consts::{
+        ALERT_DEFAULT_COOLDOWN_SECONDS, ALERT_OFFLINE_COOLDOWN_SECONDS, ALERT_OFFLINE_DEBOUNCE,
+        ALERT_RPC_COOLDOWN_SECONDS, ALERT_RPC_DEBOUNCE, ALERT_SOL_COOLDOWN_SECONDS,
+        ALERT_SOL_THRESHOLD, ALERT_WEBHOOK_TIMEOUT_SECONDS,
+    },
+    utils::{abbreviate, get_local_time},
+    Account,
+};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The conditions an alert rule can watch for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    /// The miner stopped submitting hashes.
+    Offline,
+    /// The SOL in the miner's wallet fell below the threshold.
+    LowSol,
+    /// The miner's stake reached the threshold.
+    StakeAbove,
+    /// The miner's RPC endpoint fails or is unhealthy.
+    RpcFailure,
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AlertKind::Offline => "Offline",
+                AlertKind::LowSol => "Low SOL",
+                AlertKind::StakeAbove => "Stake target",
+                AlertKind::RpcFailure => "RPC failure",
+            }
+        )
+    }
+}
+
+/// A condition watched on every account after each refresh.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AlertRule {
+    pub kind: AlertKind,
+    /// The SOL balance or stake the rule compares against.
+    #[serde(default)]
+    pub threshold: f64,
+    /// Consecutive refreshes the condition must hold before the alert fires.
+    #[serde(default = "default_debounce")]
+    pub debounce: u32,
+    /// Seconds before the alert fires again for the same account.
+    #[serde(default = "default_cooldown")]
+    pub cooldown_seconds: i64,
+}
+
+fn default_debounce() -> u32 {
+    1
+}
+
+fn default_cooldown() -> i64 {
+    ALERT_DEFAULT_COOLDOWN_SECONDS
+}
+
+/// Where alerts are delivered besides the in-app list.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AlertSinks {
+    /// A file every alert is appended to.
+    pub log_file: Option<String>,
+    /// A URL every alert is posted to as JSON.
+    pub webhook_url: Option<String>,
+    /// A shell command run for every alert, with the alert in `ALERT_*` environment variables.
+    pub command: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AlertsConfig {
+    pub rules: Vec<AlertRule>,
+    pub sinks: AlertSinks,
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        AlertsConfig {
+            rules: vec![
+                AlertRule {
+                    kind: AlertKind::Offline,
+                    threshold: 0.0,
+                    debounce: ALERT_OFFLINE_DEBOUNCE,
+                    cooldown_seconds: ALERT_OFFLINE_COOLDOWN_SECONDS,
+                },
+                AlertRule {
+                    kind: AlertKind::LowSol,
+                    threshold: ALERT_SOL_THRESHOLD,
+                    debounce: 1,
+                    cooldown_seconds: ALERT_SOL_COOLDOWN_SECONDS,
+                },
+                AlertRule {
+                    kind: AlertKind::RpcFailure,
+                    threshold: 0.0,
+                    debounce: ALERT_RPC_DEBOUNCE,
+                    cooldown_seconds: ALERT_RPC_COOLDOWN_SECONDS,
+                },
+            ],
+            sinks: AlertSinks::default(),
+        }
+    }
+}
+
+/// A fired alert.
+#[derive(Serialize, Debug, Clone)]
+pub struct Alert {
+    /// Unix timestamp of the refresh that fired the alert.
+    pub timestamp: i64,
+    pub kind: AlertKind,
+    pub account: String,
+    pub message: String,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            get_local_time(self.timestamp),
+            self.kind,
+            self.account,
+            self.message
+        )
+    }
+}
+
+/// The outcome of checking a rule against an account or endpoint.
+enum Check {
+    /// The account's data is too stale to tell.
+    Unknown,
+    Clear,
+    Triggered(String),
+}
+
+/// What a rule keeps its state for. RPC failures are tracked per endpoint, as every
+/// account on a failing endpoint fails alike, the other conditions per account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Subject {
+    Account(u64),
+    Endpoint(String),
+}
+
+impl AlertRule {
+    /// Checks the rule against every account, or every endpoint in use for RPC failures.
+    /// Returns the subject, its name in alerts and the outcome of each check.
+    fn check_all(
+        &self,
+        accounts: &[Account],
+        is_healthy: &impl Fn(&str) -> bool,
+    ) -> Vec<(Subject, String, Check)> {
+        if let AlertKind::RpcFailure = self.kind {
+            let mut urls: Vec<&str> = vec![];
+            for a in accounts {
+                if !urls.contains(&a.json_rpc_url.as_str()) {
+                    urls.push(&a.json_rpc_url);
+                }
+            }
+            return urls
+                .into_iter()
+                .map(|url| {
+                    let error = accounts
+                        .iter()
+                        .filter(|a| a.json_rpc_url == url)
+                        .find_map(|a| a.error.as_ref().filter(|e| e.is_retryable()));
+                    let check = match (error, is_healthy(url)) {
+                        (Some(e), _) => Check::Triggered(format!("Requests fail: {}", e)),
+                        (None, false) => Check::Triggered("Endpoint is unhealthy".to_string()),
+                        (None, true) => Check::Clear,
+                    };
+                    (Subject::Endpoint(url.to_string()), url.to_string(), check)
+                })
+                .collect();
+        }
+        accounts
+            .iter()
+            .map(|a| {
+                let name = abbreviate(&a.status.authority.to_string());
+                (Subject::Account(a.id), name, self.check(a))
+            })
+            .collect()
+    }
+
+    fn check(&self, account: &Account) -> Check {
+        let status = &account.status;
+        if !account.prepared || !status.is_valid || account.error.is_some() {
+            return Check::Unknown;
+        }
+        let triggered = match self.kind {
+            AlertKind::Offline => (!status.is_online)
+                .then(|| format!("No hash submitted since {}", status.last_hash_at)),
+            AlertKind::LowSol => (status.sol_balance < self.threshold).then(|| {
+                format!(
+                    "{} SOL left, below {} SOL",
+                    status.sol_balance, self.threshold
+                )
+            }),
+            AlertKind::StakeAbove => {
+                let stake = status.stake.parse::<f64>().unwrap_or(0.0);
+                (stake >= self.threshold)
+                    .then(|| format!("{} ORE staked, reached {} ORE", stake, self.threshold))
+            }
+            AlertKind::RpcFailure => None,
+        };
+        match triggered {
+            Some(message) => Check::Triggered(message),
+            None => Check::Clear,
+        }
+    }
+}
+
+/// The debounce and cooldown state of a rule for one account or endpoint.
+#[derive(Default)]
+struct RuleState {
+    /// Consecutive refreshes the condition held.
+    streak: u32,
+    last_fired: Option<i64>,
+    /// Whether the condition was seen clear since the rule last fired, so a target
+    /// fires once when it is crossed rather than every cooldown while it is held.
+    armed: bool,
+}
+
+impl RuleState {
+    /// Applies the outcome of a check, returning whether the alert fires.
+    fn update(&mut self, rule: &AlertRule, check: &Check, now: i64) -> bool {
+        match check {
+            Check::Unknown => false,
+            Check::Clear => {
+                self.streak = 0;
+                self.armed = true;
+                false
+            }
+            Check::Triggered(_) => {
+                self.streak += 1;
+                let ready = match rule.kind {
+                    // A stake target fires on the upward crossing only
+                    AlertKind::StakeAbove => self.armed,
+                    _ => self
+                        .last_fired
+                        .map_or(true, |fired| now - fired >= rule.cooldown_seconds),
+                };
+                if self.streak < rule.debounce.max(1) || !ready {
+                    return false;
+                }
+                self.last_fired = Some(now);
+                self.armed = false;
+                true
+            }
+        }
+    }
+}
+
+/// Evaluates alert rules, remembering how long conditions have held and when alerts fired.
+#[derive(Default)]
+pub struct AlertEngine {
+    /// States by rule position and subject.
+    states: HashMap<(usize, Subject), RuleState>,
+}
+
+impl AlertEngine {
+    /// Checks every rule against the accounts, returning the alerts that fire.
+    pub fn evaluate(
+        &mut self,
+        rules: &[AlertRule],
+        accounts: &[Account],
+        is_healthy: impl Fn(&str) -> bool,
+        now: i64,
+    ) -> Vec<Alert> {
+        let mut alerts = vec![];
+        for (position, rule) in rules.iter().enumerate() {
+            for (subject, name, check) in rule.check_all(accounts, &is_healthy) {
+                let state = self.states.entry((position, subject)).or_default();
+                if !state.update(rule, &check, now) {
+                    continue;
+                }
+                if let Check::Triggered(message) = check {
+                    alerts.push(Alert {
+                        timestamp: now,
+                        kind: rule.kind,
+                        account: name,
+                        message,
+                    });
+                }
+            }
+        }
+        alerts
+    }
+
+    /// Forgets the state of a removed account.
+    pub fn forget(&mut self, id: u64) {
+        self.states
+            .retain(|(_, subject), _| *subject != Subject::Account(id));
+    }
+}
+
+/// A destination alerts are delivered to.
+#[async_trait]
+pub trait AlertSink: Send + Sync {
+    async fn deliver(&self, alert: &Alert) -> Result<(), String>;
+}
+
+/// Appends alerts to a log file.
+pub struct LogFileSink {
+    path: String,
+}
+
+#[async_trait]
+impl AlertSink for LogFileSink {
+    async fn deliver(&self, alert: &Alert) -> Result<(), String> {
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open {}: {}", self.path, e))?;
+        writeln!(file, "{}", alert).map_err(|e| format!("Failed to write {}: {}", self.path, e))
+    }
+}
+
+/// Posts alerts as JSON to a webhook.
+pub struct WebhookSink {
+    url: String,
+    client: Client,
+}
+
+#[async_trait]
+impl AlertSink for WebhookSink {
+    async fn deliver(&self, alert: &Alert) -> Result<(), String> {
+        self.client
+            .post(&self.url)
+            .json(alert)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| format!("Failed to post to {}: {}", self.url, e))
+    }
+}
+
+/// Runs a local shell command for every alert.
+pub struct CommandSink {
+    command: String,
+}
+
+#[async_trait]
+impl AlertSink for CommandSink {
+    async fn deliver(&self, alert: &Alert) -> Result<(), String> {
+        let mut command = if cfg!(windows) {
+            let mut command = tokio::process::Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = tokio::process::Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        let status = command
+            .arg(&self.command)
+            .env("ALERT_KIND", format!("{:?}", alert.kind))
+            .env("ALERT_ACCOUNT", &alert.account)
+            .env("ALERT_MESSAGE", &alert.message)
+            .env("ALERT_TIMESTAMP", alert.timestamp.to_string())
+            .status()
+            .await
+            .map_err(|e| format!("Failed to run {}: {}", self.command, e))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!("{} exited with {}", self.command, status))
+        }
+    }
+}
+
+/// Builds the sinks enabled in the config, once when it is loaded. A sink that can't be
+/// built is left out.
+pub fn create_sinks(sinks: &AlertSinks) -> Vec<Arc<dyn AlertSink>> {
+    let mut created: Vec<Arc<dyn AlertSink>> = vec![];
+    if let Some(path) = &sinks.log_file {
+        created.push(Arc::new(LogFileSink { path: path.clone() }));
+    }
+    if let Some(url) = &sinks.webhook_url {
+        match Client::builder()
+            .timeout(Duration::from_secs(ALERT_WEBHOOK_TIMEOUT_SECONDS))
+            .build()
+        {
+            Ok(client) => created.push(Arc::new(WebhookSink {
+                url: url.clone(),
+                client,
+            })),
+            Err(e) => eprintln!("Failed to create the webhook client: {}", e),
+        }
+    }
+    if let Some(command) = &sinks.command {
+        created.push(Arc::new(CommandSink {
+            command: command.clone(),
+        }));
+    }
+    created
+}
+
+/// Delivers alerts to every sink, returning the errors.
+pub async fn deliver_alerts(sinks: Vec<Arc<dyn AlertSink>>, alerts: Vec<Alert>) -> Vec<String> {
+    let mut errors = vec![];
+    for alert in &alerts {
+        for sink in &sinks {
+            if let Err(e) = sink.deliver(alert).await {
+                errors.push(e);
+            }
+        }
+    }
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        balance::{FetchError, MinerStatus},
+        logic::create_account,
+        miner::Config,
+        rpc_pool::Endpoint,
+    };
+
+    fn account(id: u64, url: &str) -> Account {
+        let endpoint = Arc::new(Endpoint::new(url.to_string(), 1, 10));
+        let mut account = create_account(id, endpoint, &Config::default());
+        account.prepared = true;
+        account.status = MinerStatus {
+            is_valid: true,
+            is_online: true,
+            stake: "0".to_string(),
+            ..Default::default()
+        };
+        account
+    }
+
+    fn rule(kind: AlertKind, threshold: f64, debounce: u32, cooldown_seconds: i64) -> AlertRule {
+        AlertRule {
+            kind,
+            threshold,
+            debounce,
+            cooldown_seconds,
+        }
+    }
+
+    #[test]
+    fn alert_fires_once_the_condition_held_for_the_debounce() {
+        let rules = [rule(AlertKind::Offline, 0.0, 2, 3600)];
+        let mut engine = AlertEngine::default();
+        let mut accounts = [account(1, "http://a")];
+        accounts[0].status.is_online = false;
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 0).is_empty());
+
+        // A clear check restarts the streak
+        accounts[0].status.is_online = true;
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 60).is_empty());
+        accounts[0].status.is_online = false;
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 120).is_empty());
+        assert_eq!(engine.evaluate(&rules, &accounts, |_| true, 180).len(), 1);
+    }
+
+    #[test]
+    fn alert_repeats_only_after_the_cooldown() {
+        let rules = [rule(AlertKind::Offline, 0.0, 1, 3600)];
+        let mut engine = AlertEngine::default();
+        let mut accounts = [account(1, "http://a")];
+        accounts[0].status.is_online = false;
+        assert_eq!(engine.evaluate(&rules, &accounts, |_| true, 0).len(), 1);
+        assert!(engine
+            .evaluate(&rules, &accounts, |_| true, 3599)
+            .is_empty());
+        assert_eq!(engine.evaluate(&rules, &accounts, |_| true, 3600).len(), 1);
+    }
+
+    #[test]
+    fn stale_data_neither_fires_nor_clears() {
+        let rules = [rule(AlertKind::Offline, 0.0, 2, 3600)];
+        let mut engine = AlertEngine::default();
+        let mut accounts = [account(1, "http://a")];
+        accounts[0].status.is_online = false;
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 0).is_empty());
+        accounts[0].error = Some(FetchError::Timeout);
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 60).is_empty());
+        accounts[0].error = None;
+        assert_eq!(engine.evaluate(&rules, &accounts, |_| true, 120).len(), 1);
+    }
+
+    #[test]
+    fn rpc_failures_are_tracked_per_endpoint() {
+        let rules = [rule(AlertKind::RpcFailure, 0.0, 2, 3600)];
+        let mut engine = AlertEngine::default();
+        let mut accounts = [
+            account(1, "http://a"),
+            account(2, "http://a"),
+            account(3, "http://b"),
+        ];
+        accounts[0].error = Some(FetchError::Timeout);
+        accounts[1].error = Some(FetchError::Timeout);
+        // Two failing accounts on one endpoint don't make up the debounce together
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 0).is_empty());
+        let alerts = engine.evaluate(&rules, &accounts, |_| true, 60);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].account, "http://a");
+
+        // The cooldown holds for the endpoint, whichever account fails on it
+        accounts[0].error = None;
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 120).is_empty());
+    }
+
+    #[test]
+    fn unhealthy_endpoint_fires_without_failing_accounts() {
+        let rules = [rule(AlertKind::RpcFailure, 0.0, 1, 3600)];
+        let mut engine = AlertEngine::default();
+        let accounts = [account(1, "http://a"), account(2, "http://b")];
+        let alerts = engine.evaluate(&rules, &accounts, |url| url != "http://b", 0);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].account, "http://b");
+    }
+
+    #[test]
+    fn stake_target_fires_on_the_upward_crossing_only() {
+        let rules = [rule(AlertKind::StakeAbove, 100.0, 1, 0)];
+        let mut engine = AlertEngine::default();
+        let mut accounts = [account(1, "http://a")];
+
+        // A stake already above the target when watching starts is no crossing
+        accounts[0].status.stake = "150".to_string();
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 0).is_empty());
+
+        accounts[0].status.stake = "50".to_string();
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 60).is_empty());
+        accounts[0].status.stake = "120".to_string();
+        assert_eq!(engine.evaluate(&rules, &accounts, |_| true, 120).len(), 1);
+        // Holding above the target doesn't fire again, whatever the cooldown
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 180).is_empty());
+
+        // Dropping below the target re-arms the rule
+        accounts[0].status.stake = "90".to_string();
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 240).is_empty());
+        accounts[0].status.stake = "100".to_string();
+        assert_eq!(engine.evaluate(&rules, &accounts, |_| true, 300).len(), 1);
+    }
+
+    #[test]
+    fn forgotten_account_starts_over() {
+        let rules = [rule(AlertKind::Offline, 0.0, 2, 3600)];
+        let mut engine = AlertEngine::default();
+        let mut accounts = [account(1, "http://a")];
+        accounts[0].status.is_online = false;
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 0).is_empty());
+        engine.forget(1);
+        assert!(engine.evaluate(&rules, &accounts, |_| true, 60).is_empty());
+    }
+
+    #[test]
+    fn rules_default_their_debounce_and_cooldown() {
+        let config: AlertsConfig = toml::from_str("[[rules]]\nkind = \"Offline\"").unwrap();
+        assert_eq!(config.rules[0].debounce, 1);
+        assert_eq!(
+            config.rules[0].cooldown_seconds,
+            ALERT_DEFAULT_COOLDOWN_SECONDS
+        );
+        assert!(config.sinks.webhook_url.is_none());
+
+        let config: AlertsConfig = toml::from_str("").unwrap();
+        assert_eq!(config.rules.len(), AlertsConfig::default().rules.len());
+    }
+}
@@ -0,0 +1,267 @@
+use crate::{
+    balance::MinerStatus,
+    claim::ClaimParams,
+    consts::AUTOMATION_CAP_WINDOW_SECONDS,
+    logic::{request_claim, request_stake},
+    miner::Miner,
+    stake::StakeParams,
+    utils::amount_u64_to_f64,
+};
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// What an automation rule does with a miner's ORE.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutomationAction {
+    /// Claims the staked ORE of the proof to a wallet.
+    Claim,
+    /// Stakes the ORE of the miner's wallet back into the proof.
+    Compound,
+}
+
+impl AutomationAction {
+    /// The name the action is stored under in the history.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AutomationAction::Claim => "claim",
+            AutomationAction::Compound => "compound",
+        }
+    }
+}
+
+impl FromStr for AutomationAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "claim" => Ok(AutomationAction::Claim),
+            "compound" => Ok(AutomationAction::Compound),
+            _ => Err(format!("Unknown automation action: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for AutomationAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                AutomationAction::Claim => "Auto-claim",
+                AutomationAction::Compound => "Auto-compound",
+            }
+        )
+    }
+}
+
+/// A rule run on an account after every refresh.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutomationRule {
+    pub action: AutomationAction,
+    /// ORE above which the stake is claimed, or the wallet balance is staked.
+    pub threshold: f64,
+    /// Wallet claimed ORE is sent to, the miner's own wallet by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_address: Option<String>,
+    /// Seconds to wait after a run before the rule runs again.
+    #[serde(default)]
+    pub min_interval_seconds: i64,
+    /// ORE the rule may move within a day, no limit when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_cap: Option<f64>,
+}
+
+impl AutomationRule {
+    /// The ORE the rule would move for a miner, or `None` while it is below the threshold.
+    fn available(&self, status: &MinerStatus) -> Option<f64> {
+        let amount = match self.action {
+            AutomationAction::Claim => amount_u64_to_f64(status.stake_amount),
+            AutomationAction::Compound => status.balance.parse::<f64>().unwrap_or(0.0),
+        };
+        (amount > self.threshold).then_some(amount)
+    }
+
+    /// Decides whether the rule runs for a miner, given its earlier runs, and for how much ORE.
+    pub fn plan(&self, status: &MinerStatus, runs: &[AutomationRun], now: i64) -> Option<f64> {
+        let amount = self.available(status)?;
+        let runs = runs
+            .iter()
+            .filter(|r| r.authority == status.authority && r.action == self.action);
+        let mut moved = 0.0;
+        for run in runs {
+            if now - run.timestamp < self.min_interval_seconds {
+                return None;
+            }
+            if run.succeeded && now - run.timestamp < AUTOMATION_CAP_WINDOW_SECONDS {
+                moved += run.amount;
+            }
+        }
+        let amount = match self.daily_cap {
+            Some(cap) => amount.min(cap - moved),
+            None => amount,
+        };
+        (amount > 0.0).then_some(amount)
+    }
+}
+
+/// A completed run of an automation rule.
+#[derive(Debug, Clone)]
+pub struct AutomationRun {
+    pub authority: Pubkey,
+    /// Unix timestamp the run started at.
+    pub timestamp: i64,
+    pub action: AutomationAction,
+    /// ORE the run was meant to move.
+    pub amount: f64,
+    pub succeeded: bool,
+    /// Why the run failed, if it did.
+    pub error: Option<String>,
+}
+
+impl AutomationRun {
+    pub fn failed(
+        authority: Pubkey,
+        timestamp: i64,
+        action: AutomationAction,
+        amount: f64,
+        error: String,
+    ) -> Self {
+        AutomationRun {
+            authority,
+            timestamp,
+            action,
+            amount,
+            succeeded: false,
+            error: Some(error),
+        }
+    }
+}
+
+/// Runs a rule through the same claim and stake code as the buttons on the cards.
+pub async fn run_rule(
+    miner: Arc<Miner>,
+    rule: AutomationRule,
+    authority: Pubkey,
+    amount: f64,
+    timestamp: i64,
+) -> AutomationRun {
+    let result = match rule.action {
+        AutomationAction::Claim => {
+            let params = ClaimParams {
+                amount: Some(amount),
+                wallet_address: rule.wallet_address.clone(),
+            };
+            request_claim(miner, params).await
+        }
+        AutomationAction::Compound => {
+            let params = StakeParams {
+                amount: Some(amount),
+                sender: None,
+            };
+            request_stake(miner, params).await
+        }
+    };
+    match result {
+        Ok(_) => AutomationRun {
+            authority,
+            timestamp,
+            action: rule.action,
+            amount,
+            succeeded: true,
+            error: None,
+        },
+        Err(error) => AutomationRun::failed(authority, timestamp, rule.action, amount, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(min_interval_seconds: i64, daily_cap: Option<f64>) -> AutomationRule {
+        AutomationRule {
+            action: AutomationAction::Compound,
+            threshold: 1.0,
+            wallet_address: None,
+            min_interval_seconds,
+            daily_cap,
+        }
+    }
+
+    fn status(authority: Pubkey, balance: &str) -> MinerStatus {
+        MinerStatus {
+            is_valid: true,
+            authority,
+            balance: balance.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn run(authority: Pubkey, timestamp: i64, amount: f64, succeeded: bool) -> AutomationRun {
+        AutomationRun {
+            authority,
+            timestamp,
+            action: AutomationAction::Compound,
+            amount,
+            succeeded,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn interval_and_daily_cap_are_optional() {
+        let rule: AutomationRule = toml::from_str("action = \"Claim\"\nthreshold = 2.0").unwrap();
+        assert_eq!(rule.min_interval_seconds, 0);
+        assert_eq!(rule.daily_cap, None);
+    }
+
+    #[test]
+    fn plan_waits_for_the_threshold() {
+        let authority = Pubkey::new_unique();
+        assert_eq!(rule(0, None).plan(&status(authority, "0.5"), &[], 0), None);
+        assert_eq!(
+            rule(0, None).plan(&status(authority, "10"), &[], 0),
+            Some(10.0)
+        );
+    }
+
+    #[test]
+    fn plan_waits_for_the_interval() {
+        let authority = Pubkey::new_unique();
+        let runs = [run(authority, 1000, 2.0, true)];
+        let rule = rule(3600, None);
+        assert_eq!(rule.plan(&status(authority, "10"), &runs, 2000), None);
+        assert_eq!(rule.plan(&status(authority, "10"), &runs, 4600), Some(10.0));
+    }
+
+    #[test]
+    fn plan_caps_the_amount_moved_in_a_day() {
+        let authority = Pubkey::new_unique();
+        let window = AUTOMATION_CAP_WINDOW_SECONDS;
+        let now = window * 2;
+        let rule = rule(0, Some(5.0));
+        assert_eq!(rule.plan(&status(authority, "10"), &[], now), Some(5.0));
+
+        // Only successful runs within the window count towards the cap
+        let runs = [
+            run(authority, now - 100, 3.0, true),
+            run(authority, now - 50, 4.0, false),
+            run(authority, now - window - 1, 5.0, true),
+        ];
+        assert_eq!(rule.plan(&status(authority, "10"), &runs, now), Some(2.0));
+
+        let runs = [run(authority, now - 100, 5.0, true)];
+        assert_eq!(rule.plan(&status(authority, "10"), &runs, now), None);
+    }
+
+    #[test]
+    fn plan_ignores_runs_of_other_miners() {
+        let (authority, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let runs = [run(other, 0, 5.0, true)];
+        let rule = rule(3600, Some(5.0));
+        assert_eq!(rule.plan(&status(authority, "10"), &runs, 100), Some(5.0));
+    }
+}
@@ -1,14 +1,67 @@
 use crate::{
     consts::ACTIVE_PERIOD_SECONDS,
     miner::Miner,
-    utils::{amount_u64_to_string, get_local_time, try_get_proof},
+    utils::{
+        amount_f64_to_u64, amount_u64_to_string, calculate_multiplier, get_local_time,
+        parse_legacy_token_balance, parse_token_balance, proof_pubkey,
+    },
 };
 use chrono::{Local, TimeZone};
-use ore_api::consts::MINT_ADDRESS;
+use ore_api::{
+    consts::{CONFIG_ADDRESS, MINT_ADDRESS, MINT_V1_ADDRESS},
+    state::{Config as GlobalConfig, Proof},
+};
+use ore_utils::AccountDeserialize;
+use solana_client::client_error::{ClientError, ClientErrorKind};
 use solana_program::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
+use solana_sdk::{account::Account, native_token::lamports_to_sol, signature::Signer};
+use spl_associated_token_account::get_associated_token_address;
+use std::fmt;
 use std::str::FromStr;
 
+/// Describes why the status of a miner could not be fetched.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// The miner has no proof account on chain.
+    MissingProof(Pubkey),
+    /// The RPC node returned an error.
+    Rpc(String),
+    /// The RPC node did not answer in time.
+    Timeout,
+    /// The keypair or address of the miner is unusable.
+    InvalidKey(String),
+}
+
+impl FetchError {
+    /// Transient errors are worth retrying, the others need the user's attention.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, FetchError::Rpc(_) | FetchError::Timeout)
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::MissingProof(_) => write!(f, "Miner account doesn't exist"),
+            FetchError::Rpc(e) => write!(f, "RPC error: {}", e),
+            FetchError::Timeout => write!(f, "RPC request timed out"),
+            FetchError::InvalidKey(e) => write!(f, "Invalid key: {}", e),
+        }
+    }
+}
+
+impl From<ClientError> for FetchError {
+    fn from(e: ClientError) -> Self {
+        match e.kind() {
+            ClientErrorKind::Reqwest(e) if e.is_timeout() => FetchError::Timeout,
+            _ => FetchError::Rpc(e.to_string()),
+        }
+    }
+}
+
+/// The outcome of fetching the status of a miner.
+pub type FetchResult = Result<MinerStatus, FetchError>;
+
 /// Represents the status of a miner, including stake, balance, and activity details.
 #[derive(Default, Debug, Clone)]
 pub struct MinerStatus {
@@ -16,6 +69,12 @@ pub struct MinerStatus {
     pub is_valid: bool,
     /// The quantity of tokens this miner has staked.
     pub stake: String,
+    /// The raw stake balance of the proof.
+    pub stake_amount: u64,
+    /// The global top stake balance, which caps the multiplier.
+    pub top_balance: u64,
+    /// The current reward multiplier derived from the stake.
+    pub multiplier: f64,
     /// The signer authorized to use this proof.
     pub authority: Pubkey,
     /// The quantity of tokens this miner has earned.
@@ -26,6 +85,8 @@ pub struct MinerStatus {
     pub last_hash: [u8; 32],
     /// The last time this account provided a hash.
     pub last_hash_at: String,
+    /// The unix timestamp of the last hash.
+    pub last_hash_timestamp: i64,
     /// The last time + 60s compare to now
     pub is_online: bool,
     /// The last time stake was deposited into this account.
@@ -34,62 +95,168 @@ pub struct MinerStatus {
     pub total_hashes: u64,
     /// The total lifetime rewards distributed to this miner.
     pub total_rewards: u64,
+    /// The SOL held by the miner's wallet to pay for transactions.
+    pub sol_balance: f64,
+    /// The legacy v1 ORE held by the miner's wallet, not yet upgraded.
+    pub legacy_balance: f64,
 }
 
-impl Miner {
-    /// Retrieves the balance and status of the miner associated with the given address.
-    /// If no address is provided, the default signer address is used.    
-    pub async fn balance(&self, address: Option<String>) -> MinerStatus {
-        let signer = self.signer();
-        let address = if let Some(address) = address {
-            if let Ok(address) = Pubkey::from_str(&address) {
-                address
-            } else {
-                println!("Invalid address: {:?}", address);
-                return MinerStatus::default();
-            }
-        } else {
-            signer.pubkey()
-        };
-
-        // Try to get the proof associated with the miner's address
-        let proof = match try_get_proof(&self.rpc_client, address).await {
-            Some(proof) => proof,
-            None => {
-                let mut status = MinerStatus::default();
-                status.authority = address;
-                status.is_valid = false;
-                return status;
-            }
-        };
-
-        // Get the associated token account balance
-        let token_account_address =
-            spl_associated_token_account::get_associated_token_address(&address, &MINT_ADDRESS);
-        let token_balance = if let Ok(Some(token_account)) = self
-            .rpc_client
-            .get_token_account(&token_account_address)
-            .await
-        {
-            token_account.token_amount.ui_amount_string
-        } else {
-            "0".to_string()
-        };
+impl MinerStatus {
+    /// Builds the status of a miner from its proof account and token balance.
+    pub fn from_proof(proof: &Proof, balance: String, top_balance: u64) -> Self {
         MinerStatus {
             is_valid: true,
             authority: proof.authority,
-            balance: token_balance,
+            balance,
             stake: amount_u64_to_string(proof.balance),
+            stake_amount: proof.balance,
+            top_balance,
+            multiplier: calculate_multiplier(proof.balance, top_balance),
             challenge: proof.challenge,
             last_hash: proof.last_hash,
             last_hash_at: get_local_time(proof.last_hash_at),
-            is_online: Local::now()
-                < Local
-                    .timestamp_opt(proof.last_hash_at.saturating_add(ACTIVE_PERIOD_SECONDS), 0)
-                    .unwrap(),
+            last_hash_timestamp: proof.last_hash_at,
+            // The dashboard judges it again by the configured threshold
+            is_online: is_active(proof.last_hash_at, ACTIVE_PERIOD_SECONDS),
             last_stake_at: get_local_time(proof.last_stake_at),
             total_hashes: proof.total_hashes,
             total_rewards: proof.total_rewards,
+            sol_balance: 0.0,
+            legacy_balance: 0.0,
+        }
+    }
+
+    /// Sets the SOL balance of the miner's wallet from its lamports.
+    pub fn with_lamports(self, lamports: u64) -> Self {
+        MinerStatus {
+            sol_balance: lamports_to_sol(lamports),
+            ..self
+        }
+    }
+
+    /// Sets the legacy v1 ORE balance of the miner's wallet.
+    pub fn with_legacy_balance(self, legacy_balance: f64) -> Self {
+        MinerStatus {
+            legacy_balance,
+            ..self
         }
     }
+
+    /// Builds the status of a miner from raw proof, token, wallet and legacy token accounts.
+    pub fn from_accounts(
+        authority: Pubkey,
+        proof: Option<&Account>,
+        token: Option<&Account>,
+        wallet: Option<&Account>,
+        legacy_token: Option<&Account>,
+        top_balance: u64,
+    ) -> FetchResult {
+        let Some(proof) = proof.and_then(|a| Proof::try_from_bytes(&a.data).ok()) else {
+            return Err(FetchError::MissingProof(authority));
+        };
+        let balance = token
+            .and_then(|a| parse_token_balance(&a.data))
+            .unwrap_or_else(|| "0".to_string());
+        let lamports = wallet.map_or(0, |a| a.lamports);
+        let legacy_balance = legacy_token
+            .and_then(|a| parse_legacy_token_balance(&a.data))
+            .unwrap_or(0.0);
+        Ok(MinerStatus::from_proof(proof, balance, top_balance)
+            .with_lamports(lamports)
+            .with_legacy_balance(legacy_balance))
+    }
+
+    /// Projects the reward multiplier if an extra amount of ORE were staked.
+    pub fn projected_multiplier(&self, extra: f64) -> f64 {
+        calculate_multiplier(
+            self.stake_amount.saturating_add(amount_f64_to_u64(extra)),
+            self.top_balance,
+        )
+    }
+}
+
+impl Miner {
+    /// Retrieves the balance and status of the miner associated with the given address.
+    /// If no address is provided, the default signer address is used.
+    pub async fn balance(&self, address: Option<String>) -> FetchResult {
+        let address = match address {
+            Some(address) => Pubkey::from_str(&address)
+                .map_err(|_| FetchError::InvalidKey(format!("Invalid address: {}", address)))?,
+            None => self.try_signer().map_err(FetchError::InvalidKey)?.pubkey(),
+        };
+        let commitment = self.read_commitment.config();
+
+        // Read the proof, token accounts, wallet and global config in one request
+        let token_address = get_associated_token_address(&address, &MINT_ADDRESS);
+        let legacy_address = get_associated_token_address(&address, &MINT_V1_ADDRESS);
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts_with_commitment(
+                &[
+                    proof_pubkey(address),
+                    token_address,
+                    address,
+                    legacy_address,
+                    CONFIG_ADDRESS,
+                ],
+                commitment,
+            )
+            .await?
+            .value;
+        let account = |index: usize| accounts.get(index).and_then(Option::as_ref);
+
+        // Get the global top balance to calculate the stake multiplier
+        let top_balance = account(4)
+            .and_then(|a| GlobalConfig::try_from_bytes(&a.data).ok())
+            .map_or(0, |config| config.top_balance);
+        MinerStatus::from_accounts(
+            address,
+            account(0),
+            account(1),
+            account(2),
+            account(3),
+            top_balance,
+        )
+    }
+}
+
+/// Checks whether a miner submitted a hash within the active period.
+pub fn is_active(last_hash_at: i64, active_period: i64) -> bool {
+    Local::now()
+        < Local
+            .timestamp_opt(last_hash_at.saturating_add(active_period), 0)
+            .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projected_multiplier_adds_the_extra_stake() {
+        let status = MinerStatus {
+            stake_amount: amount_f64_to_u64(10.0),
+            top_balance: amount_f64_to_u64(100.0),
+            ..Default::default()
+        };
+        assert_eq!(status.projected_multiplier(0.0), 1.1);
+        assert_eq!(status.projected_multiplier(40.0), 1.5);
+        assert_eq!(status.projected_multiplier(1000.0), 2.0);
+    }
+
+    #[test]
+    fn missing_proof_is_reported_for_the_authority() {
+        let authority = Pubkey::new_unique();
+        assert_eq!(
+            MinerStatus::from_accounts(authority, None, None, None, None, 0).unwrap_err(),
+            FetchError::MissingProof(authority)
+        );
+    }
+
+    #[test]
+    fn miners_are_active_within_the_period() {
+        let now = Local::now().timestamp();
+        assert!(is_active(now - 60, 70));
+        assert!(!is_active(now - 60, 30));
+    }
 }
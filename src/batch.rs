@@ -0,0 +1,92 @@
+use crate::{
+    balance::{FetchError, FetchResult, MinerStatus},
+    cluster::Commitment,
+    consts::FETCH_TIMEOUT_SECONDS,
+    rpc_pool::Endpoint,
+    utils::proof_pubkey,
+};
+use ore_api::{
+    consts::{CONFIG_ADDRESS, MINT_ADDRESS, MINT_V1_ADDRESS},
+    state::Config as GlobalConfig,
+};
+use ore_utils::AccountDeserialize;
+use solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use spl_associated_token_account::get_associated_token_address;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fetches the status of every miner sharing an RPC endpoint with chunked
+/// `getMultipleAccounts` calls, decoding the proofs and token balances locally.
+/// The miners share the commitment the accounts are read with.
+pub async fn fetch_batch(
+    endpoint: Arc<Endpoint>,
+    commitment: Commitment,
+    miners: Vec<(u64, Pubkey)>,
+) -> Vec<(u64, FetchResult)> {
+    // Lay out the global config followed by the proof, token account, wallet and
+    // legacy token account of every miner
+    let mut addresses = vec![CONFIG_ADDRESS];
+    for (_, authority) in &miners {
+        addresses.push(proof_pubkey(*authority));
+        addresses.push(get_associated_token_address(authority, &MINT_ADDRESS));
+        addresses.push(*authority);
+        addresses.push(get_associated_token_address(authority, &MINT_V1_ADDRESS));
+    }
+
+    // Keep the outcome of each chunk, so a failed chunk only fails its own miners
+    let mut accounts: Vec<Result<Option<Account>, FetchError>> =
+        Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let _permit = endpoint.acquire().await;
+        let response = tokio::time::timeout(
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS),
+            endpoint
+                .rpc_client
+                .get_multiple_accounts_with_commitment(chunk, commitment.config()),
+        )
+        .await;
+        match response {
+            Ok(Ok(chunk_accounts)) => accounts.extend(chunk_accounts.value.into_iter().map(Ok)),
+            Ok(Err(e)) => {
+                eprintln!("Failed to fetch accounts from {}: {}", endpoint.url, e);
+                let e = FetchError::from(e);
+                accounts.extend(std::iter::repeat(Err(e)).take(chunk.len()));
+            }
+            Err(_) => {
+                accounts.extend(std::iter::repeat(Err(FetchError::Timeout)).take(chunk.len()))
+            }
+        }
+    }
+
+    let top_balance = accounts[0]
+        .as_ref()
+        .ok()
+        .and_then(|a| a.as_ref())
+        .and_then(|a| GlobalConfig::try_from_bytes(&a.data).ok())
+        .map(|config| config.top_balance)
+        .unwrap_or(0);
+    miners
+        .into_iter()
+        .zip(accounts[1..].chunks(4))
+        .map(|((id, authority), miner_accounts)| {
+            let result = match miner_accounts {
+                [Ok(proof), Ok(token), Ok(wallet), Ok(legacy)] => MinerStatus::from_accounts(
+                    authority,
+                    proof.as_ref(),
+                    token.as_ref(),
+                    wallet.as_ref(),
+                    legacy.as_ref(),
+                    top_balance,
+                ),
+                _ => Err(miner_accounts
+                    .iter()
+                    .find_map(|a| a.as_ref().err())
+                    .cloned()
+                    .unwrap_or(FetchError::Timeout)),
+            };
+            (id, result)
+        })
+        .collect()
+}
@@ -0,0 +1,396 @@
+use crate::{
+    consts::{
+        BALANCE_PRECISION, CHART_LABEL_SIZE, CHART_MARGIN_BOTTOM, CHART_MARGIN_LEFT, CHART_PADDING,
+    },
+    history::{PricePoint, Snapshot},
+    uptime::Uptime,
+    utils::{amount_u64_to_f64, round_dp},
+};
+use chrono::{Local, TimeZone};
+use iced::widget::canvas::{self, Path, Stroke, Text};
+use iced::{mouse, Color, Point, Rectangle, Renderer, Size, Theme};
+use std::fmt;
+
+const SECONDS_PER_HOUR: i64 = 3600;
+
+/// The time ranges charts can be drawn over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChartRange {
+    Day,
+    Week,
+    Month,
+    Quarter,
+}
+
+impl ChartRange {
+    pub const ALL: [ChartRange; 4] = [
+        ChartRange::Day,
+        ChartRange::Week,
+        ChartRange::Month,
+        ChartRange::Quarter,
+    ];
+
+    pub fn seconds(&self) -> i64 {
+        let days = match self {
+            ChartRange::Day => 1,
+            ChartRange::Week => 7,
+            ChartRange::Month => 30,
+            ChartRange::Quarter => 90,
+        };
+        days * 24 * SECONDS_PER_HOUR
+    }
+}
+
+impl fmt::Display for ChartRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ChartRange::Day => "24 hours",
+                ChartRange::Week => "7 days",
+                ChartRange::Month => "30 days",
+                ChartRange::Quarter => "90 days",
+            }
+        )
+    }
+}
+
+/// The ranges the price chart can be drawn over, which the backfill covers.
+pub const PRICE_RANGES: [ChartRange; 3] = [ChartRange::Day, ChartRange::Week, ChartRange::Month];
+
+/// How the points of a chart are drawn.
+#[derive(Debug, Clone, Copy)]
+pub enum ChartKind {
+    /// A line through every point.
+    Line,
+    /// A bar per hour, ending at the point's timestamp.
+    Bars,
+}
+
+/// A time-series chart over a fixed time range.
+pub struct Chart {
+    title: String,
+    kind: ChartKind,
+    /// Unix timestamps and values, oldest first.
+    points: Vec<(i64, f64)>,
+    from: i64,
+    to: i64,
+}
+
+impl Chart {
+    pub fn new(title: &str, kind: ChartKind, points: Vec<(i64, f64)>, from: i64, to: i64) -> Self {
+        Chart {
+            title: title.to_string(),
+            kind,
+            points,
+            from,
+            to: to.max(from + 1),
+        }
+    }
+
+    /// Charts the staked ORE of the snapshots.
+    pub fn stake(snapshots: &[Snapshot], from: i64, to: i64) -> Self {
+        let points = snapshots.iter().map(|s| (s.timestamp, s.stake)).collect();
+        Chart::new("Stake (ORE)", ChartKind::Line, points, from, to)
+    }
+
+    /// Charts the lifetime rewards of the snapshots.
+    pub fn rewards(snapshots: &[Snapshot], from: i64, to: i64) -> Self {
+        let points = snapshots
+            .iter()
+            .map(|s| (s.timestamp, amount_u64_to_f64(s.total_rewards)))
+            .collect();
+        Chart::new("Total rewards (ORE)", ChartKind::Line, points, from, to)
+    }
+
+    /// Charts the hashes submitted per hour, derived from the growth of the total hashes.
+    pub fn hash_rate(snapshots: &[Snapshot], from: i64, to: i64) -> Self {
+        let points = hashes_per_hour(snapshots)
+            .into_iter()
+            .map(|(hour, hashes)| (hour + SECONDS_PER_HOUR, hashes as f64))
+            .collect();
+        Chart::new("Hashes per hour", ChartKind::Bars, points, from, to)
+    }
+
+    /// Charts the stored prices of ORE in their currency.
+    pub fn price(points: &[PricePoint], currency: &str, from: i64, to: i64) -> Self {
+        let points = points
+            .iter()
+            .filter(|p| p.timestamp >= from)
+            .map(|p| (p.timestamp, p.ore))
+            .collect();
+        let title = format!("ORE price ({})", currency.to_uppercase());
+        Chart::new(&title, ChartKind::Line, points, from, to)
+    }
+
+    fn x(&self, plot: &Rectangle, timestamp: i64) -> f32 {
+        let ratio = (timestamp - self.from) as f32 / (self.to - self.from) as f32;
+        plot.x + ratio.clamp(0.0, 1.0) * plot.width
+    }
+}
+
+/// Sums the hashes submitted between consecutive snapshots into hours. The hashes of a gap
+/// between two snapshots are spread evenly across the hours it covers, so a gap after a
+/// restart or downsampling doesn't pile them into a single hour.
+pub fn hashes_per_hour(snapshots: &[Snapshot]) -> Vec<(i64, u64)> {
+    let mut buckets: Vec<(i64, u64)> = vec![];
+    let mut add = |hour: i64, hashes: u64| match buckets.last_mut() {
+        Some((last, total)) if *last == hour => *total += hashes,
+        _ => buckets.push((hour, hashes)),
+    };
+    for pair in snapshots.windows(2) {
+        let (start, end) = (pair[0].timestamp, pair[1].timestamp);
+        let hashes = pair[1].total_hashes.saturating_sub(pair[0].total_hashes);
+        if end <= start {
+            add(end / SECONDS_PER_HOUR * SECONDS_PER_HOUR, hashes);
+            continue;
+        }
+        let mut assigned = 0;
+        let mut from = start;
+        while from < end {
+            let hour = from / SECONDS_PER_HOUR * SECONDS_PER_HOUR;
+            let to = (hour + SECONDS_PER_HOUR).min(end);
+            // The last hour takes what rounding left over
+            let share = if to == end {
+                hashes - assigned
+            } else {
+                (hashes as u128 * (to - from) as u128 / (end - start) as u128) as u64
+            };
+            assigned += share;
+            add(hour, share);
+            from = to;
+        }
+    }
+    buckets
+}
+
+fn format_label_time(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(time) => time.format("%m-%d %H:%M").to_string(),
+        None => String::default(),
+    }
+}
+
+impl<Message> canvas::Program<Message> for Chart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let text_color = palette.background.base.text;
+        let plot = Rectangle {
+            x: CHART_MARGIN_LEFT,
+            y: CHART_PADDING,
+            width: (bounds.width - CHART_MARGIN_LEFT - CHART_PADDING).max(1.0),
+            height: (bounds.height - CHART_PADDING - CHART_MARGIN_BOTTOM).max(1.0),
+        };
+        let label = |content: String, position: Point| Text {
+            content,
+            position,
+            color: text_color,
+            size: CHART_LABEL_SIZE.into(),
+            ..Text::default()
+        };
+
+        frame.fill_text(label(self.title.clone(), Point::new(plot.x + 5.0, 0.0)));
+        let axes = Path::new(|b| {
+            b.move_to(Point::new(plot.x, plot.y));
+            b.line_to(Point::new(plot.x, plot.y + plot.height));
+            b.line_to(Point::new(plot.x + plot.width, plot.y + plot.height));
+        });
+        frame.stroke(
+            &axes,
+            Stroke::default()
+                .with_color(palette.background.strong.color)
+                .with_width(1.0),
+        );
+        let bottom = plot.y + plot.height + 2.0;
+        frame.fill_text(label(
+            format_label_time(self.from),
+            Point::new(plot.x, bottom),
+        ));
+        frame.fill_text(label(
+            format_label_time(self.to),
+            Point::new(plot.x + plot.width - 65.0, bottom),
+        ));
+
+        if self.points.is_empty() {
+            frame.fill_text(label(
+                "No data in this range".to_string(),
+                Point::new(plot.x + plot.width / 2.0 - 50.0, plot.y + plot.height / 2.0),
+            ));
+            return vec![frame.into_geometry()];
+        }
+
+        // Bars grow from zero, while lines zoom into the range of their values
+        let mut min = match self.kind {
+            ChartKind::Line => self.points.iter().map(|p| p.1).fold(f64::MAX, f64::min),
+            ChartKind::Bars => 0.0,
+        };
+        let mut max = self.points.iter().map(|p| p.1).fold(f64::MIN, f64::max);
+        if max - min < f64::EPSILON {
+            min -= 1.0_f64.min(min.abs());
+            max += 1.0;
+        }
+        let y =
+            |value: f64| plot.y + plot.height - ((value - min) / (max - min)) as f32 * plot.height;
+        frame.fill_text(label(
+            round_dp(max, BALANCE_PRECISION).to_string(),
+            Point::new(0.0, plot.y),
+        ));
+        frame.fill_text(label(
+            round_dp(min, BALANCE_PRECISION).to_string(),
+            Point::new(0.0, plot.y + plot.height - CHART_LABEL_SIZE),
+        ));
+
+        let color = palette.primary.base.color;
+        match self.kind {
+            ChartKind::Line => {
+                let line = Path::new(|b| {
+                    let (t, v) = self.points[0];
+                    b.move_to(Point::new(self.x(&plot, t), y(v)));
+                    for (t, v) in &self.points[1..] {
+                        b.line_to(Point::new(self.x(&plot, *t), y(*v)));
+                    }
+                });
+                frame.stroke(&line, Stroke::default().with_color(color).with_width(2.0));
+            }
+            ChartKind::Bars => {
+                for (t, v) in &self.points {
+                    let left = self.x(&plot, t - SECONDS_PER_HOUR);
+                    let right = self.x(&plot, *t);
+                    let top = y(*v);
+                    frame.fill_rectangle(
+                        Point::new(left, top),
+                        Size::new((right - left - 1.0).max(1.0), plot.y + plot.height - top),
+                        color,
+                    );
+                }
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+/// A bar showing when a miner was online, offline or not tracked.
+pub struct Timeline {
+    uptime: Uptime,
+}
+
+impl Timeline {
+    pub fn new(uptime: Uptime) -> Self {
+        Timeline { uptime }
+    }
+}
+
+impl<Message> canvas::Program<Message> for Timeline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let (from, to) = (self.uptime.from, self.uptime.to.max(self.uptime.from + 1));
+        let x = |timestamp: i64| {
+            let ratio = (timestamp - from) as f32 / (to - from) as f32;
+            ratio.clamp(0.0, 1.0) * bounds.width
+        };
+        let span = |frame: &mut canvas::Frame, start: i64, end: i64, color: Color| {
+            frame.fill_rectangle(
+                Point::new(x(start), 0.0),
+                Size::new((x(end) - x(start)).max(1.0), bounds.height),
+                color,
+            );
+        };
+
+        // Untracked time stays in the background color
+        span(&mut frame, from, to, palette.background.strong.color);
+        if self.uptime.tracked_seconds > 0 {
+            span(
+                &mut frame,
+                self.uptime.tracked_from(),
+                to,
+                palette.success.base.color,
+            );
+        }
+        for (start, end) in &self.uptime.outages {
+            span(&mut frame, *start, *end, palette.danger.base.color);
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::pubkey::Pubkey;
+
+    fn snapshot(timestamp: i64, total_hashes: u64) -> Snapshot {
+        Snapshot {
+            authority: Pubkey::default(),
+            timestamp,
+            balance: 0.0,
+            stake: 0.0,
+            total_hashes,
+            total_rewards: 0,
+            last_hash_at: timestamp,
+            online: true,
+        }
+    }
+
+    #[test]
+    fn hashes_within_an_hour_are_summed() {
+        let snapshots = [
+            snapshot(0, 0),
+            snapshot(600, 10),
+            snapshot(1200, 30),
+            snapshot(3600, 35),
+            snapshot(4200, 40),
+        ];
+        assert_eq!(hashes_per_hour(&snapshots), vec![(0, 35), (3600, 5)]);
+    }
+
+    #[test]
+    fn hashes_of_a_gap_are_spread_across_the_hours_it_covers() {
+        let snapshots = [snapshot(0, 0), snapshot(3 * SECONDS_PER_HOUR, 300)];
+        assert_eq!(
+            hashes_per_hour(&snapshots),
+            vec![(0, 100), (3600, 100), (7200, 100)]
+        );
+    }
+
+    #[test]
+    fn rounding_leftovers_go_to_the_last_hour_of_a_gap() {
+        let snapshots = [snapshot(1800, 0), snapshot(1800 + 2 * SECONDS_PER_HOUR, 10)];
+        let buckets = hashes_per_hour(&snapshots);
+        assert_eq!(buckets, vec![(0, 2), (3600, 5), (7200, 3)]);
+        assert_eq!(buckets.iter().map(|(_, hashes)| hashes).sum::<u64>(), 10);
+    }
+
+    #[test]
+    fn price_chart_starts_at_the_range() {
+        let point = |timestamp: i64, ore: f64| PricePoint {
+            timestamp,
+            currency: "eur".to_string(),
+            ore,
+            sol: None,
+        };
+        let chart = Chart::price(&[point(50, 1.0), point(150, 2.0)], "eur", 100, 200);
+        assert_eq!(chart.title, "ORE price (EUR)");
+        assert_eq!(chart.points, vec![(150, 2.0)]);
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
 };
 use ore_api::consts::MINT_ADDRESS;
 use solana_program::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
+use solana_sdk::signature::{Signature, Signer};
 use std::str::FromStr;
 
 #[derive(Default, Debug, Clone)]
@@ -18,17 +18,16 @@ pub struct ClaimParams {
 }
 
 impl Miner {
-    pub async fn claim(&self, params: ClaimParams) -> bool {
-        let signer = self.signer();
-        let pubkey = signer.pubkey();
-        let proof = get_proof(&self.rpc_client, pubkey).await;
+    pub async fn claim(&self, params: ClaimParams) -> Result<Signature, String> {
+        let pubkey = self.try_signer()?.pubkey();
+        let proof = get_proof(&self.rpc_client, pubkey).await?;
         let beneficiary = match params.wallet_address {
             Some(wallet_address) => {
-                let to_pubkey =
-                    Pubkey::from_str(&wallet_address).expect("Failed to parse claim address");
-                self.initialize_ata(&to_pubkey).await
+                let to_pubkey = Pubkey::from_str(&wallet_address)
+                    .map_err(|_| format!("Invalid wallet address: {}", wallet_address))?;
+                self.initialize_ata(&to_pubkey).await?
             }
-            None => self.initialize_ata(&pubkey).await,
+            None => self.initialize_ata(&pubkey).await?,
         };
         let amount = if let Some(amount) = params.amount {
             amount_f64_to_u64(amount)
@@ -39,11 +38,10 @@ impl Miner {
         let ix = ore_api::instruction::claim(pubkey, beneficiary, amount);
         self.send_and_confirm(&[ix], ComputeBudget::Fixed(CU_LIMIT_CLAIM), false)
             .await
-            .ok();
-        true
+            .map_err(|e| format!("Claim failed: {}", e))
     }
 
-    async fn initialize_ata(&self, pubkey: &Pubkey) -> Pubkey {
+    pub async fn initialize_ata(&self, pubkey: &Pubkey) -> Result<Pubkey, String> {
         // Initialize client.
         let client = self.rpc_client.clone();
         // Build instructions.
@@ -51,10 +49,10 @@ impl Miner {
             spl_associated_token_account::get_associated_token_address(&pubkey, &MINT_ADDRESS);
         // Check if ata already exists
         if let Ok(Some(_ata)) = client.get_token_account(&token_account_pubkey).await {
-            return token_account_pubkey;
+            return Ok(token_account_pubkey);
         }
         // Sign and send transaction.
-        let payer = self.signer().pubkey();
+        let payer = self.try_signer()?.pubkey();
         let ix = spl_associated_token_account::instruction::create_associated_token_account(
             &payer,
             &pubkey,
@@ -63,9 +61,9 @@ impl Miner {
         );
         self.send_and_confirm(&[ix], ComputeBudget::Dynamic, false)
             .await
-            .ok();
+            .map_err(|e| format!("Failed to create token account: {}", e))?;
 
         // Return token account address
-        token_account_pubkey
+        Ok(token_account_pubkey)
     }
 }
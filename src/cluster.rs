@@ -0,0 +1,261 @@
+use crate::consts::{DEVNET_GENESIS_HASH, MAINNET_BETA_GENESIS_HASH, TESTNET_GENESIS_HASH};
+use serde::{Deserialize, Serialize};
+use solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use solana_sdk::hash::Hash;
+use solana_transaction_status::TransactionConfirmationStatus;
+use std::fmt;
+use url::Url;
+
+/// The Solana clusters an RPC endpoint can serve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cluster {
+    MainnetBeta,
+    Devnet,
+    Testnet,
+    Localnet,
+    /// An endpoint of an unknown cluster.
+    Custom,
+}
+
+impl Cluster {
+    pub const ALL: [Cluster; 5] = [
+        Cluster::MainnetBeta,
+        Cluster::Devnet,
+        Cluster::Testnet,
+        Cluster::Localnet,
+        Cluster::Custom,
+    ];
+
+    /// The public endpoint of a preset.
+    pub fn url(&self) -> Option<&'static str> {
+        match self {
+            Cluster::MainnetBeta => Some("https://api.mainnet-beta.solana.com"),
+            Cluster::Devnet => Some("https://api.devnet.solana.com"),
+            Cluster::Testnet => Some("https://api.testnet.solana.com"),
+            Cluster::Localnet => Some("http://localhost:8899"),
+            Cluster::Custom => None,
+        }
+    }
+
+    /// The URL to show once the cluster is picked. Picking custom clears a preset URL,
+    /// so another one can be entered, but keeps a custom one.
+    pub fn select_url(&self, current: &str) -> String {
+        match self.url() {
+            Some(url) => url.to_string(),
+            None if Cluster::from_url(current) == Cluster::Custom => current.to_string(),
+            None => String::default(),
+        }
+    }
+
+    /// Tells the cluster from the host of a public endpoint or a local validator.
+    pub fn from_url(url: &str) -> Self {
+        let host = |url: &str| Url::parse(url).ok()?.host_str().map(str::to_string);
+        match host(url).as_deref() {
+            None => Cluster::Custom,
+            Some("localhost" | "127.0.0.1" | "0.0.0.0") => Cluster::Localnet,
+            Some(h) => Cluster::ALL
+                .into_iter()
+                .find(|c| c.url().and_then(host).as_deref() == Some(h))
+                .unwrap_or(Cluster::Custom),
+        }
+    }
+
+    /// Tells the cluster from the genesis hash of its ledger, which also identifies
+    /// endpoints of RPC providers.
+    pub fn from_genesis_hash(genesis_hash: &Hash) -> Option<Self> {
+        match genesis_hash.to_string().as_str() {
+            MAINNET_BETA_GENESIS_HASH => Some(Cluster::MainnetBeta),
+            DEVNET_GENESIS_HASH => Some(Cluster::Devnet),
+            TESTNET_GENESIS_HASH => Some(Cluster::Testnet),
+            _ => None,
+        }
+    }
+
+    /// Tells the cluster of an endpoint by its genesis hash once it's known, else by its URL.
+    pub fn detect(url: &str, genesis_hash: Option<&Hash>) -> Self {
+        genesis_hash
+            .and_then(Cluster::from_genesis_hash)
+            .unwrap_or_else(|| Cluster::from_url(url))
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Cluster::MainnetBeta => "mainnet-beta",
+                Cluster::Devnet => "devnet",
+                Cluster::Testnet => "testnet",
+                Cluster::Localnet => "localnet",
+                Cluster::Custom => "custom",
+            }
+        )
+    }
+}
+
+/// How settled the state read or the transaction confirmed must be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    Processed,
+    #[default]
+    Confirmed,
+    Finalized,
+}
+
+impl Commitment {
+    pub const ALL: [Commitment; 3] = [
+        Commitment::Processed,
+        Commitment::Confirmed,
+        Commitment::Finalized,
+    ];
+
+    pub fn is_default(&self) -> bool {
+        *self == Commitment::default()
+    }
+
+    pub fn level(&self) -> CommitmentLevel {
+        match self {
+            Commitment::Processed => CommitmentLevel::Processed,
+            Commitment::Confirmed => CommitmentLevel::Confirmed,
+            Commitment::Finalized => CommitmentLevel::Finalized,
+        }
+    }
+
+    pub fn config(&self) -> CommitmentConfig {
+        CommitmentConfig {
+            commitment: self.level(),
+        }
+    }
+
+    /// The commitment to fetch a blockhash at. A processed blockhash may belong to a fork
+    /// that is skipped, which makes the transaction fail, so it is at least confirmed.
+    pub fn for_blockhash(&self) -> Commitment {
+        match self {
+            Commitment::Processed => Commitment::Confirmed,
+            other => *other,
+        }
+    }
+
+    /// Checks whether a transaction with the given status is settled enough.
+    pub fn is_reached(&self, status: &TransactionConfirmationStatus) -> bool {
+        match status {
+            TransactionConfirmationStatus::Processed => *self == Commitment::Processed,
+            TransactionConfirmationStatus::Confirmed => *self != Commitment::Finalized,
+            TransactionConfirmationStatus::Finalized => true,
+        }
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Commitment::Processed => "Processed",
+                Commitment::Confirmed => "Confirmed",
+                Commitment::Finalized => "Finalized",
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn cluster_is_told_from_the_host() {
+        assert_eq!(
+            Cluster::from_url("https://api.mainnet-beta.solana.com/"),
+            Cluster::MainnetBeta
+        );
+        assert_eq!(
+            Cluster::from_url("https://api.devnet.solana.com"),
+            Cluster::Devnet
+        );
+        assert_eq!(
+            Cluster::from_url("http://127.0.0.1:8899"),
+            Cluster::Localnet
+        );
+        assert_eq!(
+            Cluster::from_url("https://rpc.example.com"),
+            Cluster::Custom
+        );
+        assert_eq!(Cluster::from_url("not a url"), Cluster::Custom);
+    }
+
+    #[test]
+    fn genesis_hash_wins_over_the_url() {
+        let mainnet = Hash::from_str(MAINNET_BETA_GENESIS_HASH).unwrap();
+        assert_eq!(
+            Cluster::detect("https://rpc.example.com", Some(&mainnet)),
+            Cluster::MainnetBeta
+        );
+        assert_eq!(
+            Cluster::detect("https://api.devnet.solana.com", Some(&Hash::default())),
+            Cluster::Devnet
+        );
+    }
+
+    #[test]
+    fn picking_custom_keeps_only_a_custom_url() {
+        assert_eq!(
+            Cluster::Devnet.select_url("https://rpc.example.com"),
+            "https://api.devnet.solana.com"
+        );
+        assert_eq!(
+            Cluster::Custom.select_url("https://rpc.example.com"),
+            "https://rpc.example.com"
+        );
+        assert_eq!(
+            Cluster::Custom.select_url("https://api.devnet.solana.com"),
+            ""
+        );
+    }
+
+    #[test]
+    fn commitment_is_reached_at_its_level_or_above() {
+        use TransactionConfirmationStatus::{Confirmed, Finalized, Processed};
+        assert!(Commitment::Processed.is_reached(&Processed));
+        assert!(Commitment::Processed.is_reached(&Confirmed));
+        assert!(Commitment::Processed.is_reached(&Finalized));
+        assert!(!Commitment::Confirmed.is_reached(&Processed));
+        assert!(Commitment::Confirmed.is_reached(&Confirmed));
+        assert!(Commitment::Confirmed.is_reached(&Finalized));
+        assert!(!Commitment::Finalized.is_reached(&Processed));
+        assert!(!Commitment::Finalized.is_reached(&Confirmed));
+        assert!(Commitment::Finalized.is_reached(&Finalized));
+    }
+
+    #[test]
+    fn blockhash_is_at_least_confirmed() {
+        assert_eq!(Commitment::Processed.for_blockhash(), Commitment::Confirmed);
+        assert_eq!(Commitment::Confirmed.for_blockhash(), Commitment::Confirmed);
+        assert_eq!(Commitment::Finalized.for_blockhash(), Commitment::Finalized);
+    }
+
+    #[test]
+    fn default_commitment_is_not_written_to_the_config() {
+        #[derive(Serialize)]
+        struct Account {
+            #[serde(skip_serializing_if = "Commitment::is_default")]
+            read_commitment: Commitment,
+        }
+        let confirmed = Account {
+            read_commitment: Commitment::Confirmed,
+        };
+        assert_eq!(toml::to_string(&confirmed).unwrap(), "");
+        let finalized = Account {
+            read_commitment: Commitment::Finalized,
+        };
+        assert_eq!(
+            toml::to_string(&finalized).unwrap(),
+            "read_commitment = \"finalized\"\n"
+        );
+    }
+}
@@ -0,0 +1,389 @@
+use crate::consts::{
+    APP_DIR_NAME, CONFIG_BACKUPS, CONFIG_VERSION, DEFAULT_PROFILE, USER_CONFIG_FILE,
+};
+use crate::miner::Profile;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use toml::{Table, Value};
+
+/// A forward migration of the config file, upgrading it from the version before.
+type Migration = fn(&mut Table);
+
+/// Migrations by the version they upgrade to, starting from version 1.
+const MIGRATIONS: [Migration; CONFIG_VERSION as usize] = [add_required_fields, move_to_profiles];
+
+/// The path of the user config in the platform config directory, e.g.
+/// `~/.config/ore-bar/user-config.toml` on Linux. Falls back to the working
+/// directory where the platform has no config directory.
+pub fn config_path() -> PathBuf {
+    match dirs::config_dir() {
+        Some(dir) => dir.join(APP_DIR_NAME).join(USER_CONFIG_FILE),
+        None => PathBuf::from(USER_CONFIG_FILE),
+    }
+}
+
+/// The profile named in the command line arguments with `--profile <name>` or `-p <name>`.
+pub fn cli_profile(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let name = match arg.strip_prefix("--profile=") {
+            Some(name) => Some(name.to_string()),
+            None if arg == "--profile" || arg == "-p" => args.next(),
+            None => continue,
+        };
+        return name.filter(|name| !name.trim().is_empty());
+    }
+    None
+}
+
+/// Checks that a profile named on the command line is one of the profiles of the config,
+/// so a mistyped name doesn't start an empty profile. A config without profiles has
+/// only the default one.
+pub fn check_profile(name: &str, profiles: &[Profile]) -> Result<(), String> {
+    let mut names: Vec<&str> = profiles.iter().map(|p| p.name.as_str()).collect();
+    if names.is_empty() {
+        names.push(DEFAULT_PROFILE);
+    }
+    if names.contains(&name) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown profile {}, pick one of: {}",
+            name,
+            names.join(", ")
+        ))
+    }
+}
+
+/// Copies a file left in the working directory by earlier versions to its platform
+/// directory, unless one is there already. The old file is left in place.
+pub fn migrate_location(file: &str, path: &Path) {
+    let legacy = Path::new(file);
+    if path.exists() || !legacy.is_file() || path == legacy {
+        return;
+    }
+    let copied = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::copy(legacy, path));
+    match copied {
+        Ok(_) => println!("Copied {} to {}", file, path.display()),
+        Err(e) => eprintln!("Failed to copy {} to {}: {}", file, path.display(), e),
+    }
+}
+
+/// Appends a suffix to the file name of a path.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// The path of the n-th most recent backup of a config, e.g. `user-config.toml.1`.
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    with_suffix(path, &format!(".{}", n))
+}
+
+/// Writes a file through a temporary file renamed over it, so a crash or a full
+/// disk leaves either the old or the new content, never a truncated file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temp = with_suffix(path, ".tmp");
+    {
+        let mut file = File::create(&temp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&temp, path)
+}
+
+/// Backs up a config before it is replaced, keeping the last `CONFIG_BACKUPS`.
+/// Configs that don't parse are never backed up, so they can't push good backups out,
+/// and neither are configs that the new content would leave unchanged.
+pub fn back_up(path: &Path, contents: &str) -> io::Result<()> {
+    let Ok(current) = fs::read_to_string(path) else {
+        return Ok(());
+    };
+    if current == contents || toml::from_str::<Table>(&current).is_err() {
+        return Ok(());
+    }
+    for n in (1..CONFIG_BACKUPS).rev() {
+        let backup = backup_path(path, n);
+        if backup.exists() {
+            fs::rename(&backup, backup_path(path, n + 1))?;
+        }
+    }
+    write_atomically(&backup_path(path, 1), current.as_bytes())
+}
+
+/// The backups of a config with when they were written, most recent first.
+pub fn backups(path: &Path) -> Vec<(PathBuf, SystemTime)> {
+    (1..=CONFIG_BACKUPS)
+        .map(|n| backup_path(path, n))
+        .filter_map(|backup| {
+            let modified = fs::metadata(&backup).and_then(|m| m.modified()).ok()?;
+            Some((backup, modified))
+        })
+        .collect()
+}
+
+/// Replaces a config with one of its backups, keeping the replaced file next to it
+/// with a `.corrupt` suffix.
+pub fn restore_backup(path: &Path, backup: &Path) -> io::Result<()> {
+    let contents = fs::read(backup)?;
+    set_aside(path)?;
+    write_atomically(path, &contents)
+}
+
+/// Moves a config that couldn't be loaded out of the way, next to it with a `.corrupt`
+/// suffix, so saving doesn't overwrite it.
+pub fn set_aside(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        fs::rename(path, with_suffix(path, ".corrupt"))?;
+    }
+    Ok(())
+}
+
+/// Upgrades a parsed config file to the current version, returning whether it changed.
+/// Files written by a newer version are left as they are.
+pub fn upgrade(config: &mut Table) -> bool {
+    let version = config
+        .get("version")
+        .and_then(Value::as_integer)
+        .unwrap_or(0)
+        .max(0) as u32;
+    if version > CONFIG_VERSION {
+        eprintln!(
+            "Config version {} is newer than the supported version {}",
+            version, CONFIG_VERSION
+        );
+        return false;
+    }
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(config);
+    }
+    config.insert("version".to_string(), Value::Integer(CONFIG_VERSION as i64));
+    version < CONFIG_VERSION
+}
+
+/// Version 1: files of the earliest versions may lack fields that have no default.
+fn add_required_fields(config: &mut Table) {
+    config
+        .entry("configs")
+        .or_insert_with(|| Value::Array(vec![]));
+    config
+        .entry("theme")
+        .or_insert_with(|| Value::String("Light".to_string()));
+    if let Some(Value::Array(accounts)) = config.get_mut("configs") {
+        for account in accounts.iter_mut().filter_map(Value::as_table_mut) {
+            account.entry("priority_fee").or_insert(Value::Integer(0));
+        }
+    }
+}
+
+/// Version 2: accounts and settings move into a profile, so that several sets of
+/// accounts can be kept apart.
+fn move_to_profiles(config: &mut Table) {
+    let mut profile = Table::new();
+    profile.insert(
+        "name".to_string(),
+        Value::String(DEFAULT_PROFILE.to_string()),
+    );
+    for key in ["configs", "settings"] {
+        if let Some(value) = config.remove(key) {
+            profile.insert(key.to_string(), value);
+        }
+    }
+    config.insert(
+        "profile".to_string(),
+        Value::String(DEFAULT_PROFILE.to_string()),
+    );
+    config.insert(
+        "profiles".to_string(),
+        Value::Array(vec![Value::Table(profile)]),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::miner::Configs;
+
+    fn upgraded(contents: &str) -> (bool, Configs) {
+        let mut table: Table = toml::from_str(contents).unwrap();
+        let changed = upgrade(&mut table);
+        (changed, Value::Table(table).try_into().unwrap())
+    }
+
+    #[test]
+    fn upgrades_version_0_to_current() {
+        let (changed, configs) = upgraded(
+            r#"
+            [[configs]]
+            json_rpc_url = "https://api.mainnet-beta.solana.com"
+            keypair_path = "id.json"
+            "#,
+        );
+        assert!(changed);
+        assert_eq!(configs.version, CONFIG_VERSION);
+        assert_eq!(configs.theme, "Light");
+        assert_eq!(configs.profile, DEFAULT_PROFILE);
+        assert_eq!(configs.profiles.len(), 1);
+        let profile = &configs.profiles[0];
+        assert_eq!(profile.name, DEFAULT_PROFILE);
+        assert_eq!(profile.configs.len(), 1);
+        assert_eq!(profile.configs[0].keypair_path, "id.json");
+        assert_eq!(profile.configs[0].priority_fee, 0);
+    }
+
+    #[test]
+    fn upgrades_version_1_to_current() {
+        let (changed, configs) = upgraded(
+            r#"
+            version = 1
+            theme = "Dark"
+
+            [[configs]]
+            json_rpc_url = "https://api.devnet.solana.com"
+            keypair_path = "devnet.json"
+            priority_fee = 5000
+
+            [settings]
+            data_interval = 30
+            "#,
+        );
+        assert!(changed);
+        assert_eq!(configs.version, CONFIG_VERSION);
+        assert_eq!(configs.theme, "Dark");
+        assert_eq!(configs.profile, DEFAULT_PROFILE);
+        let profile = &configs.profiles[0];
+        assert_eq!(profile.configs[0].priority_fee, 5000);
+        assert_eq!(profile.settings.data_interval, 30);
+    }
+
+    #[test]
+    fn leaves_current_and_newer_versions_alone() {
+        let current = format!("version = {}\ntheme = \"Dark\"\n", CONFIG_VERSION);
+        let (changed, configs) = upgraded(&current);
+        assert!(!changed);
+        assert!(configs.profiles.is_empty());
+
+        let mut newer: Table =
+            toml::from_str(&format!("version = {}", CONFIG_VERSION + 1)).unwrap();
+        assert!(!upgrade(&mut newer));
+        assert!(!newer.contains_key("profiles"));
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn cli_profile_takes_every_form_of_the_option() {
+        assert_eq!(
+            cli_profile(args(&["--profile", "devnet"])),
+            Some("devnet".to_string())
+        );
+        assert_eq!(
+            cli_profile(args(&["--profile=devnet"])),
+            Some("devnet".to_string())
+        );
+        assert_eq!(
+            cli_profile(args(&["-v", "-p", "devnet"])),
+            Some("devnet".to_string())
+        );
+        assert_eq!(cli_profile(args(&["devnet"])), None);
+        assert_eq!(cli_profile(args(&["--profile"])), None);
+        assert_eq!(cli_profile(args(&["--profile= "])), None);
+    }
+
+    #[test]
+    fn check_profile_rejects_unknown_profiles() {
+        assert!(check_profile(DEFAULT_PROFILE, &[]).is_ok());
+        assert_eq!(
+            check_profile("mainnet", &[]).unwrap_err(),
+            "Unknown profile mainnet, pick one of: default"
+        );
+        let profiles = [
+            Profile::new("mainnet", Default::default()),
+            Profile::new("devnet", Default::default()),
+        ];
+        assert!(check_profile("devnet", &profiles).is_ok());
+        assert_eq!(
+            check_profile(DEFAULT_PROFILE, &profiles).unwrap_err(),
+            "Unknown profile default, pick one of: mainnet, devnet"
+        );
+    }
+
+    /// A fresh directory for a test, so tests running in parallel don't share files.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ore-bar-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrate_location_copies_without_overwriting() {
+        let dir = test_dir("migrate-location");
+        let legacy = dir.join("legacy.toml");
+        fs::write(&legacy, "theme = \"Dark\"\n").unwrap();
+        let path = dir.join("platform").join(USER_CONFIG_FILE);
+        migrate_location(legacy.to_str().unwrap(), &path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "theme = \"Dark\"\n");
+        assert!(legacy.exists());
+
+        fs::write(&legacy, "theme = \"Light\"\n").unwrap();
+        migrate_location(legacy.to_str().unwrap(), &path);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "theme = \"Dark\"\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn back_up_keeps_the_most_recent_versions() {
+        let dir = test_dir("back-up-rotation");
+        let path = dir.join(USER_CONFIG_FILE);
+        for n in 0..=CONFIG_BACKUPS + 1 {
+            let contents = format!("theme = \"{}\"\n", n);
+            back_up(&path, &contents).unwrap();
+            write_atomically(&path, contents.as_bytes()).unwrap();
+        }
+        // The oldest version was pushed out, the newest is in backup 1
+        for n in 1..=CONFIG_BACKUPS {
+            let backup = fs::read_to_string(backup_path(&path, n)).unwrap();
+            assert_eq!(backup, format!("theme = \"{}\"\n", CONFIG_BACKUPS + 1 - n));
+        }
+        assert!(!backup_path(&path, CONFIG_BACKUPS + 1).exists());
+        assert_eq!(backups(&path).len(), CONFIG_BACKUPS);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn back_up_skips_unchanged_and_invalid_configs() {
+        let dir = test_dir("back-up-skips");
+        let path = dir.join(USER_CONFIG_FILE);
+        fs::write(&path, "theme = \"Dark\"\n").unwrap();
+        back_up(&path, "theme = \"Dark\"\n").unwrap();
+        assert!(!backup_path(&path, 1).exists());
+
+        fs::write(&path, "theme = ").unwrap();
+        back_up(&path, "theme = \"Dark\"\n").unwrap();
+        assert!(!backup_path(&path, 1).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restore_backup_sets_the_unreadable_config_aside() {
+        let dir = test_dir("restore-backup");
+        let path = dir.join(USER_CONFIG_FILE);
+        fs::write(&path, "theme = ").unwrap();
+        fs::write(backup_path(&path, 1), "theme = \"Dark\"\n").unwrap();
+        restore_backup(&path, &backup_path(&path, 1)).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "theme = \"Dark\"\n");
+        let corrupt = dir.join(format!("{}.corrupt", USER_CONFIG_FILE));
+        assert_eq!(fs::read_to_string(corrupt).unwrap(), "theme = ");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,18 +2,116 @@
 pub const MAX_ITEMS_PER_ROW: u8 = 2;
 /// User's setting file
 pub const USER_CONFIG_FILE: &str = "user-config.toml";
+/// Directory of ORE-BAR's files within the platform directories
+pub const APP_DIR_NAME: &str = "ore-bar";
+/// Version of the config file format, raised with every migration
+pub const CONFIG_VERSION: u32 = 2;
+/// Profile accounts are kept in when none is chosen
+pub const DEFAULT_PROFILE: &str = "default";
+/// Previous versions of the user config kept as backups
+pub const CONFIG_BACKUPS: usize = 5;
+/// Local database of miner status snapshots
+pub const HISTORY_DB_FILE: &str = "history.db";
+pub const HISTORY_FULL_RESOLUTION_DAYS: u32 = 2;
+pub const HISTORY_DOWNSAMPLE_MINUTES: u32 = 60;
+pub const HISTORY_RETENTION_DAYS: u32 = 90;
+pub const HISTORY_COMPACT_INTERVAL_SECONDS: i64 = 3600;
+/// Recent history the earnings rate is estimated from
+pub const EARNINGS_WINDOW_SECONDS: i64 = 86400;
+/// Shortest history an earnings rate is estimated from
+pub const EARNINGS_MIN_SPAN_SECONDS: i64 = 600;
 pub const SOLANA_DEFAULT_KEYPAIR: &str = ".config/solana/id.json";
 pub const WINDOW_SIZE: (f32, f32) = (933.0, 595.0);
+/// Default seconds after the last hash a miner still counts as online
 pub const ACTIVE_PERIOD_SECONDS: i64 = 70;
+/// Default seconds between refreshes
+pub const DATA_INTERVAL_SECONDS: u64 = 60;
+/// Default seconds between saves of a changed config
+pub const SAVE_INTERVAL_SECONDS: u64 = 5;
+/// Genesis hashes telling the public clusters apart
+pub const MAINNET_BETA_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+pub const DEVNET_GENESIS_HASH: &str = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+pub const TESTNET_GENESIS_HASH: &str = "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY";
+/// RPC endpoint suggested for new accounts by default
+pub const DEFAULT_RPC_URL: &str = "https://api.devnet.solana.com";
+/// Priority fee suggested for new accounts by default
+pub const DEFAULT_PRIORITY_FEE: u64 = 10;
+pub const FETCH_TIMEOUT_SECONDS: u64 = 20;
+pub const REFRESH_GRACE_SECONDS: u64 = 5;
+pub const RETRY_BASE_SECONDS: u64 = 2;
+pub const RETRY_MAX_ATTEMPTS: u32 = 5;
+pub const RPC_MAX_CONCURRENT_REQUESTS: usize = 8;
+pub const RPC_REQUESTS_PER_SECOND: u32 = 10;
+/// RPC requests made to fetch the status of one miner
+pub const BALANCE_REQUESTS: u32 = 1;
+pub const SUBSCRIPTION_CHANNEL_SIZE: usize = 100;
+pub const SUBSCRIPTION_RETRY_MAX_SECONDS: u64 = 60;
+pub const HEALTH_CHECK_INTERVAL_SECONDS: u64 = 30;
+/// Recent requests an endpoint's error rate is computed over
+pub const HEALTH_ERROR_WINDOW: usize = 20;
+pub const HEALTH_MAX_ERROR_RATE: f64 = 0.5;
+/// Slots an endpoint may fall behind its cluster before it is unhealthy
+pub const HEALTH_MAX_SLOT_LAG: u64 = 50;
+/// Consecutive failures of an account before it switches to its next endpoint
+pub const FAILOVER_MAX_FAILURES: u32 = 3;
+/// Consecutive healthy probes of a primary endpoint before accounts switch back to it
+pub const FAILOVER_RECOVERY_PROBES: u32 = 3;
+/// Alerts kept in the in-app list
+pub const ALERT_LIST_SIZE: usize = 100;
+pub const ALERT_OFFLINE_DEBOUNCE: u32 = 2;
+pub const ALERT_OFFLINE_COOLDOWN_SECONDS: i64 = 3600;
+/// SOL left in a miner's wallet below which it can no longer pay for transactions
+pub const ALERT_SOL_THRESHOLD: f64 = 0.01;
+pub const ALERT_SOL_COOLDOWN_SECONDS: i64 = 21600;
+pub const ALERT_RPC_DEBOUNCE: u32 = 3;
+pub const ALERT_RPC_COOLDOWN_SECONDS: i64 = 3600;
+/// Cooldown of alert rules that don't set one
+pub const ALERT_DEFAULT_COOLDOWN_SECONDS: i64 = 3600;
+pub const ALERT_WEBHOOK_TIMEOUT_SECONDS: u64 = 10;
+/// Period the daily cap of an automation rule applies to
+pub const AUTOMATION_CAP_WINDOW_SECONDS: i64 = 86400;
+/// Automation runs kept in memory to enforce intervals and caps
+pub const AUTOMATION_LOG_SECONDS: i64 = 7 * 86400;
+/// Automation runs listed in the log
+pub const AUTOMATION_LOG_SIZE: usize = 100;
+/// Interval at which scheduled jobs are checked for being due
+pub const SCHEDULER_TICK_SECONDS: u64 = 15;
+pub const SCHEDULE_UPCOMING_RUNS: usize = 10;
+/// Scheduled runs kept in the in-app history
+pub const SCHEDULE_LOG_SIZE: usize = 100;
 pub const MENU_ITEM_SPACING: u16 = 10;
 pub const MENU_CATEGORY_SPACING: u16 = 10;
 pub const MENU_SPAN_HEIGHT: u16 = 35;
 pub const MENU_ITEM_INDENT: u16 = 15;
 pub const ACCOUNT_DETAIL_WIDTH: u16 = 330;
-pub const ACCOUNT_DETAIL_HIGHT: u16 = 290;
+pub const ACCOUNT_DETAIL_HIGHT: u16 = 310;
 pub const SHOW_RPC_URL_MAX_LENGTH: usize = 32;
 pub const BALANCE_PRECISION: u8 = 4;
-pub const USD_PRECISION: u8 = 2;
+/// Decimal places of fiat values
+pub const FIAT_PRECISION: u8 = 2;
+/// Decimal places of values in SOL and other coins
+pub const CRYPTO_PRECISION: u8 = 6;
+pub const MULTIPLIER_PRECISION: u8 = 4;
 pub const SUBHEAD_TEXT: u16 = 12;
+pub const CHART_HEIGHT: u16 = 150;
+pub const TIMELINE_HEIGHT: u16 = 12;
+pub const CHART_LABEL_SIZE: f32 = 11.0;
+/// Space left of a chart's plot for the value labels
+pub const CHART_MARGIN_LEFT: f32 = 60.0;
+/// Space below a chart's plot for the time labels
+pub const CHART_MARGIN_BOTTOM: f32 = 16.0;
+pub const CHART_PADDING: f32 = 14.0;
+pub const PRICE_TIMEOUT_SECONDS: u64 = 10;
+/// Responding price sources the price is the median of
+pub const PRICE_QUORUM: usize = 2;
+/// Minimum time between prices stored in the history
+pub const PRICE_RECORD_INTERVAL_SECONDS: i64 = 300;
+/// How far back the portfolio change is measured
+pub const PRICE_CHANGE_SECONDS: i64 = 86400;
+/// How much older than the moment it prices a stored price may be
+pub const PRICE_TOLERANCE_SECONDS: i64 = 3600;
+/// Days of prices kept in memory and backfilled from the market chart
+pub const PRICE_HISTORY_DAYS: u32 = 30;
 pub const ORE_TOKEN_ID: &str = "ore";
+pub const SOL_TOKEN_ID: &str = "solana";
 pub const USD_CURRENCY: &str = "usd";
@@ -0,0 +1,119 @@
+use crate::{consts::EARNINGS_MIN_SPAN_SECONDS, history::Snapshot, utils::amount_u64_to_f64};
+use std::ops::Add;
+
+/// The recent reward rate of a miner, or of the fleet when added up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Earnings {
+    /// ORE earned per hour.
+    pub per_hour: f64,
+    /// ORE earned over the measured span.
+    pub rewards: f64,
+    /// Hashes submitted over the measured span.
+    pub hashes: u64,
+}
+
+impl Earnings {
+    /// Derives the rates from the growth of the rewards and hashes between the oldest
+    /// and newest snapshot, once they are far enough apart to be meaningful.
+    pub fn from_snapshots(snapshots: &[Snapshot]) -> Option<Self> {
+        let (first, last) = (snapshots.first()?, snapshots.last()?);
+        let seconds = last.timestamp - first.timestamp;
+        if seconds < EARNINGS_MIN_SPAN_SECONDS {
+            return None;
+        }
+        let rewards = amount_u64_to_f64(last.total_rewards.saturating_sub(first.total_rewards));
+        Some(Earnings {
+            per_hour: rewards / seconds as f64 * 3600.0,
+            rewards,
+            hashes: last.total_hashes.saturating_sub(first.total_hashes),
+        })
+    }
+
+    pub fn per_day(&self) -> f64 {
+        self.per_hour * 24.0
+    }
+
+    /// The average reward of a submitted hash.
+    pub fn per_hash(&self) -> f64 {
+        if self.hashes > 0 {
+            self.rewards / self.hashes as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Add for Earnings {
+    type Output = Earnings;
+
+    fn add(self, other: Earnings) -> Earnings {
+        Earnings {
+            per_hour: self.per_hour + other.per_hour,
+            rewards: self.rewards + other.rewards,
+            hashes: self.hashes + other.hashes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::amount_f64_to_u64;
+    use solana_program::pubkey::Pubkey;
+
+    fn snapshot(timestamp: i64, total_rewards: f64, total_hashes: u64) -> Snapshot {
+        Snapshot {
+            authority: Pubkey::default(),
+            timestamp,
+            balance: 0.0,
+            stake: 0.0,
+            total_hashes,
+            total_rewards: amount_f64_to_u64(total_rewards),
+            last_hash_at: timestamp,
+            online: true,
+        }
+    }
+
+    #[test]
+    fn rates_come_from_the_oldest_and_newest_snapshot() {
+        let snapshots = [
+            snapshot(0, 1.0, 100),
+            snapshot(3600, 1.5, 150),
+            snapshot(7200, 3.0, 500),
+        ];
+        let earnings = Earnings::from_snapshots(&snapshots).unwrap();
+        assert_eq!(earnings.rewards, 2.0);
+        assert_eq!(earnings.per_hour, 1.0);
+        assert_eq!(earnings.per_day(), 24.0);
+        assert_eq!(earnings.hashes, 400);
+        assert_eq!(earnings.per_hash(), 0.005);
+    }
+
+    #[test]
+    fn short_spans_give_no_rates() {
+        let snapshots = [
+            snapshot(0, 1.0, 100),
+            snapshot(EARNINGS_MIN_SPAN_SECONDS - 1, 2.0, 200),
+        ];
+        assert!(Earnings::from_snapshots(&snapshots).is_none());
+        assert!(Earnings::from_snapshots(&[]).is_none());
+    }
+
+    #[test]
+    fn fleet_earnings_add_up() {
+        let a = Earnings {
+            per_hour: 1.0,
+            rewards: 2.0,
+            hashes: 0,
+        };
+        let b = Earnings {
+            per_hour: 0.5,
+            rewards: 1.0,
+            hashes: 10,
+        };
+        let total = a + b;
+        assert_eq!(total.per_hour, 1.5);
+        assert_eq!(total.rewards, 3.0);
+        assert_eq!(total.per_hash(), 0.1);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    consts::{
+        FAILOVER_RECOVERY_PROBES, FETCH_TIMEOUT_SECONDS, HEALTH_ERROR_WINDOW,
+        HEALTH_MAX_ERROR_RATE, HEALTH_MAX_SLOT_LAG,
+    },
+    rpc_pool::Endpoint,
+};
+use solana_sdk::hash::Hash;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The outcome of probing an RPC endpoint once.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub url: String,
+    /// The round trip of `getSlot`, if it answered.
+    pub latency: Option<Duration>,
+    pub slot: Option<u64>,
+    /// Whether `getHealth` reported the node as healthy.
+    pub node_healthy: bool,
+    /// Identifies the cluster, so slots are only compared within one cluster.
+    pub genesis_hash: Option<Hash>,
+}
+
+/// The health of an RPC endpoint, built from periodic probes and the outcome of recent requests.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointHealth {
+    pub latency: Option<Duration>,
+    pub slot: Option<u64>,
+    /// How far the endpoint is behind the highest slot seen in its cluster.
+    pub slot_lag: u64,
+    pub node_healthy: bool,
+    pub genesis_hash: Option<Hash>,
+    /// Consecutive probes that found the endpoint healthy.
+    pub healthy_streak: u32,
+    /// Recent request outcomes, `true` for a failure.
+    outcomes: VecDeque<bool>,
+}
+
+impl EndpointHealth {
+    /// Records the outcome of a request, keeping a sliding window of recent ones.
+    pub fn record(&mut self, failed: bool) {
+        if self.outcomes.len() >= HEALTH_ERROR_WINDOW {
+            self.outcomes.pop_front();
+        }
+        self.outcomes.push_back(failed);
+        if failed {
+            self.healthy_streak = 0;
+        }
+    }
+
+    pub fn error_rate(&self) -> f64 {
+        if self.outcomes.is_empty() {
+            return 0.0;
+        }
+        self.outcomes.iter().filter(|failed| **failed).count() as f64 / self.outcomes.len() as f64
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.node_healthy
+            && self.latency.is_some()
+            && self.slot_lag <= HEALTH_MAX_SLOT_LAG
+            && self.error_rate() <= HEALTH_MAX_ERROR_RATE
+    }
+
+    /// Checks whether the endpoint has stayed healthy long enough to be trusted again.
+    pub fn is_recovered(&self) -> bool {
+        self.healthy_streak >= FAILOVER_RECOVERY_PROBES
+    }
+}
+
+/// Applies a probe to the health of its endpoint and refreshes the slot lag of its cluster.
+pub fn apply_probe(health: &mut HashMap<String, EndpointHealth>, probe: Probe) {
+    let url = probe.url.clone();
+    let entry = health.entry(probe.url).or_default();
+    entry.record(probe.latency.is_none());
+    entry.latency = probe.latency;
+    entry.slot = probe.slot.or(entry.slot);
+    entry.node_healthy = probe.node_healthy;
+    entry.genesis_hash = probe.genesis_hash.or(entry.genesis_hash);
+
+    // Compare every endpoint with the highest slot seen in the same cluster
+    let mut highest: HashMap<Hash, u64> = HashMap::new();
+    for h in health.values() {
+        if let (Some(genesis_hash), Some(slot)) = (h.genesis_hash, h.slot) {
+            let top = highest.entry(genesis_hash).or_default();
+            *top = (*top).max(slot);
+        }
+    }
+    for h in health.values_mut() {
+        h.slot_lag = match (h.genesis_hash, h.slot) {
+            (Some(genesis_hash), Some(slot)) => highest[&genesis_hash].saturating_sub(slot),
+            _ => 0,
+        };
+    }
+
+    let entry = health
+        .get_mut(&url)
+        .expect("Probed endpoint was just recorded");
+    entry.healthy_streak = if entry.is_healthy() {
+        entry.healthy_streak + 1
+    } else {
+        0
+    };
+}
+
+/// Measures the latency, slot, health and cluster of an endpoint.
+pub async fn probe(endpoint: Arc<Endpoint>) -> Probe {
+    let _permit = endpoint.acquire().await;
+    let client = &endpoint.rpc_client;
+    let timeout = Duration::from_secs(FETCH_TIMEOUT_SECONDS);
+
+    let start = Instant::now();
+    let slot = match tokio::time::timeout(timeout, client.get_slot()).await {
+        Ok(Ok(slot)) => Some(slot),
+        _ => None,
+    };
+    let latency = slot.map(|_| start.elapsed());
+    endpoint.pace().await;
+    let node_healthy = matches!(
+        tokio::time::timeout(timeout, client.get_health()).await,
+        Ok(Ok(()))
+    );
+    endpoint.pace().await;
+    let genesis_hash = match tokio::time::timeout(timeout, client.get_genesis_hash()).await {
+        Ok(Ok(hash)) => Some(hash),
+        _ => None,
+    };
+    Probe {
+        url: endpoint.url.clone(),
+        latency,
+        slot,
+        node_healthy,
+        genesis_hash,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(url: &str, slot: Option<u64>, genesis_hash: Hash) -> Probe {
+        Probe {
+            url: url.to_string(),
+            latency: slot.map(|_| Duration::from_millis(100)),
+            slot,
+            node_healthy: slot.is_some(),
+            genesis_hash: Some(genesis_hash),
+        }
+    }
+
+    #[test]
+    fn slot_lag_is_measured_within_a_cluster() {
+        let (mainnet, devnet) = (Hash::new_unique(), Hash::new_unique());
+        let mut health = HashMap::new();
+        apply_probe(&mut health, probe("a", Some(1000), mainnet));
+        apply_probe(&mut health, probe("b", Some(900), mainnet));
+        apply_probe(&mut health, probe("c", Some(10), devnet));
+        assert_eq!(health["a"].slot_lag, 0);
+        assert_eq!(health["b"].slot_lag, 100);
+        assert_eq!(health["c"].slot_lag, 0);
+    }
+
+    #[test]
+    fn failed_probe_keeps_the_last_slot_and_counts_as_an_error() {
+        let mainnet = Hash::new_unique();
+        let mut health = HashMap::new();
+        apply_probe(&mut health, probe("a", Some(1000), mainnet));
+        assert!(health["a"].is_healthy());
+        apply_probe(&mut health, probe("a", None, mainnet));
+        assert_eq!(health["a"].slot, Some(1000));
+        assert_eq!(health["a"].error_rate(), 0.5);
+        assert!(!health["a"].is_healthy());
+    }
+
+    #[test]
+    fn endpoint_recovers_after_consecutive_healthy_probes() {
+        let mainnet = Hash::new_unique();
+        let mut health = HashMap::new();
+        for _ in 1..FAILOVER_RECOVERY_PROBES {
+            apply_probe(&mut health, probe("a", Some(1000), mainnet));
+        }
+        assert!(!health["a"].is_recovered());
+        apply_probe(&mut health, probe("a", None, mainnet));
+        apply_probe(&mut health, probe("a", Some(1000), mainnet));
+        assert!(!health["a"].is_recovered());
+        for _ in 1..FAILOVER_RECOVERY_PROBES {
+            apply_probe(&mut health, probe("a", Some(1000), mainnet));
+        }
+        assert!(health["a"].is_recovered());
+    }
+
+    #[test]
+    fn error_rate_only_covers_recent_requests() {
+        let mut health = EndpointHealth::default();
+        health.record(true);
+        for _ in 0..HEALTH_ERROR_WINDOW {
+            health.record(false);
+        }
+        assert_eq!(health.error_rate(), 0.0);
+    }
+}
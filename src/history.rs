@@ -0,0 +1,654 @@
+use crate::{
+    automation::{AutomationAction, AutomationRun},
+    balance::MinerStatus,
+    consts::{APP_DIR_NAME, HISTORY_DB_FILE},
+    miner::Retention,
+    scheduler::{ScheduleRun, ScheduledAction},
+    uptime::Transition,
+};
+use rusqlite::{params, types::Type, Connection, OptionalExtension};
+use solana_program::pubkey::Pubkey;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// The status of a miner at one point in time.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub authority: Pubkey,
+    /// Unix timestamp of the refresh that produced the snapshot.
+    pub timestamp: i64,
+    pub balance: f64,
+    pub stake: f64,
+    pub total_hashes: u64,
+    pub total_rewards: u64,
+    /// Unix timestamp of the last hash.
+    pub last_hash_at: i64,
+    pub online: bool,
+}
+
+impl Snapshot {
+    pub fn from_status(status: &MinerStatus, timestamp: i64) -> Self {
+        Snapshot {
+            authority: status.authority,
+            timestamp,
+            balance: status.balance.parse::<f64>().unwrap_or(0.0),
+            stake: status.stake.parse::<f64>().unwrap_or(0.0),
+            total_hashes: status.total_hashes,
+            total_rewards: status.total_rewards,
+            last_hash_at: status.last_hash_timestamp,
+            online: status.is_online,
+        }
+    }
+}
+
+/// The path of the history database in the platform data directory, e.g.
+/// `~/.local/share/ore-bar/history.db` on Linux. Falls back to the working
+/// directory where the platform has no data directory.
+pub fn history_path() -> PathBuf {
+    match dirs::data_dir() {
+        Some(dir) => dir.join(APP_DIR_NAME).join(HISTORY_DB_FILE),
+        None => PathBuf::from(HISTORY_DB_FILE),
+    }
+}
+
+/// The prices of ORE and SOL in a currency at one point in time.
+#[derive(Debug, Clone)]
+pub struct PricePoint {
+    /// Unix timestamp of the quote.
+    pub timestamp: i64,
+    pub currency: String,
+    pub ore: f64,
+    pub sol: Option<f64>,
+}
+
+/// Snapshots of miner statuses, their online transitions, automation runs,
+/// scheduled runs and prices, kept in a local SQLite database across restarts.
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Opens the database at the given path, creating it on first use.
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        if let Some(dir) = path.parent() {
+            // A missing directory surfaces as the open error below
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS snapshots (
+                authority TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                balance REAL NOT NULL,
+                stake REAL NOT NULL,
+                total_hashes INTEGER NOT NULL,
+                total_rewards INTEGER NOT NULL,
+                last_hash_at INTEGER NOT NULL,
+                online INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS snapshots_authority_timestamp
+                ON snapshots (authority, timestamp);
+            CREATE TABLE IF NOT EXISTS transitions (
+                authority TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                online INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS transitions_authority_timestamp
+                ON transitions (authority, timestamp);
+            CREATE TABLE IF NOT EXISTS automation_runs (
+                authority TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                amount REAL NOT NULL,
+                succeeded INTEGER NOT NULL,
+                error TEXT
+            );
+            CREATE INDEX IF NOT EXISTS automation_runs_timestamp
+                ON automation_runs (timestamp);
+            CREATE TABLE IF NOT EXISTS schedule_runs (
+                job TEXT NOT NULL,
+                authority TEXT NOT NULL,
+                timestamp INTEGER NOT NULL,
+                action TEXT NOT NULL,
+                amount REAL,
+                succeeded INTEGER NOT NULL,
+                error TEXT
+            );
+            CREATE INDEX IF NOT EXISTS schedule_runs_timestamp
+                ON schedule_runs (timestamp);
+            CREATE TABLE IF NOT EXISTS prices (
+                timestamp INTEGER NOT NULL,
+                currency TEXT NOT NULL,
+                ore REAL NOT NULL,
+                sol REAL
+            );
+            CREATE INDEX IF NOT EXISTS prices_currency_timestamp
+                ON prices (currency, timestamp);",
+        )?;
+        Ok(History { conn })
+    }
+
+    /// Stores the snapshots of one refresh.
+    pub fn record(&mut self, snapshots: &[Snapshot]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO snapshots (authority, timestamp, balance, stake, total_hashes,
+                    total_rewards, last_hash_at, online)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for s in snapshots {
+                insert.execute(params![
+                    s.authority.to_string(),
+                    s.timestamp,
+                    s.balance,
+                    s.stake,
+                    s.total_hashes as i64,
+                    s.total_rewards as i64,
+                    s.last_hash_at,
+                    s.online,
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Returns the snapshots of a miner taken between `from` and `to`, oldest first.
+    pub fn query(&self, authority: &Pubkey, from: i64, to: i64) -> rusqlite::Result<Vec<Snapshot>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT authority, timestamp, balance, stake, total_hashes, total_rewards,
+                last_hash_at, online
+            FROM snapshots
+            WHERE authority = ?1 AND timestamp BETWEEN ?2 AND ?3
+            ORDER BY timestamp",
+        )?;
+        let rows = select.query_map(params![authority.to_string(), from, to], |row| {
+            let authority: String = row.get(0)?;
+            Ok(Snapshot {
+                authority: Pubkey::from_str(&authority).unwrap_or_default(),
+                timestamp: row.get(1)?,
+                balance: row.get(2)?,
+                stake: row.get(3)?,
+                total_hashes: row.get::<_, i64>(4)? as u64,
+                total_rewards: row.get::<_, i64>(5)? as u64,
+                last_hash_at: row.get(6)?,
+                online: row.get(7)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Records the online state of a miner if it differs from the last recorded one.
+    /// A transition never predates the previous one.
+    pub fn record_online(
+        &self,
+        authority: &Pubkey,
+        timestamp: i64,
+        online: bool,
+    ) -> rusqlite::Result<()> {
+        let authority = authority.to_string();
+        let last: Option<(i64, bool)> = self
+            .conn
+            .query_row(
+                "SELECT timestamp, online FROM transitions
+                WHERE authority = ?1
+                ORDER BY timestamp DESC, rowid DESC
+                LIMIT 1",
+                [&authority],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        let timestamp = match last {
+            Some((_, last_online)) if last_online == online => return Ok(()),
+            Some((last_timestamp, _)) => timestamp.max(last_timestamp),
+            None => timestamp,
+        };
+        self.conn.execute(
+            "INSERT INTO transitions (authority, timestamp, online) VALUES (?1, ?2, ?3)",
+            params![authority, timestamp, online],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the online transitions of a miner between `from` and `to`, preceded by
+    /// the last one before `from`, which gives the state at the start of the range.
+    pub fn transitions(
+        &self,
+        authority: &Pubkey,
+        from: i64,
+        to: i64,
+    ) -> rusqlite::Result<Vec<Transition>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT timestamp, online FROM (
+                SELECT timestamp, online FROM transitions
+                WHERE authority = ?1 AND timestamp < ?2
+                ORDER BY timestamp DESC, rowid DESC
+                LIMIT 1
+            )
+            UNION ALL
+            SELECT timestamp, online FROM transitions
+            WHERE authority = ?1 AND timestamp BETWEEN ?2 AND ?3
+            ORDER BY timestamp",
+        )?;
+        let rows = select.query_map(params![authority.to_string(), from, to], |row| {
+            Ok(Transition {
+                timestamp: row.get(0)?,
+                online: row.get(1)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Logs a run of an automation rule.
+    pub fn record_run(&self, run: &AutomationRun) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO automation_runs (authority, timestamp, action, amount, succeeded, error)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                run.authority.to_string(),
+                run.timestamp,
+                run.action.as_str(),
+                run.amount,
+                run.succeeded,
+                run.error,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the automation runs of every miner since `from`, oldest first.
+    pub fn runs(&self, from: i64) -> rusqlite::Result<Vec<AutomationRun>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT authority, timestamp, action, amount, succeeded, error
+            FROM automation_runs
+            WHERE timestamp >= ?1
+            ORDER BY timestamp",
+        )?;
+        let rows = select.query_map([from], |row| {
+            let authority: String = row.get(0)?;
+            let action: String = row.get(2)?;
+            Ok(AutomationRun {
+                authority: Pubkey::from_str(&authority).unwrap_or_default(),
+                timestamp: row.get(1)?,
+                action: AutomationAction::from_str(&action).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(2, Type::Text, e.into())
+                })?,
+                amount: row.get(3)?,
+                succeeded: row.get(4)?,
+                error: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Logs a run of a scheduled job.
+    pub fn record_schedule_run(&self, run: &ScheduleRun) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO schedule_runs (job, authority, timestamp, action, amount, succeeded, error)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                run.job,
+                run.authority.to_string(),
+                run.timestamp,
+                run.action.as_str(),
+                run.amount,
+                run.succeeded,
+                run.error,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Returns the latest runs of scheduled jobs, newest first.
+    pub fn schedule_runs(&self, limit: usize) -> rusqlite::Result<Vec<ScheduleRun>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT job, authority, timestamp, action, amount, succeeded, error
+            FROM schedule_runs
+            ORDER BY timestamp DESC, rowid DESC
+            LIMIT ?1",
+        )?;
+        let rows = select.query_map([limit as i64], |row| {
+            let authority: String = row.get(1)?;
+            let action: String = row.get(3)?;
+            Ok(ScheduleRun {
+                job: row.get(0)?,
+                authority: Pubkey::from_str(&authority).unwrap_or_default(),
+                timestamp: row.get(2)?,
+                action: ScheduledAction::from_str(&action).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(3, Type::Text, e.into())
+                })?,
+                amount: row.get(4)?,
+                succeeded: row.get(5)?,
+                error: row.get(6)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Stores a price unless one in the same currency was stored less than
+    /// `interval` seconds before it.
+    pub fn record_price(&self, point: &PricePoint, interval: i64) -> rusqlite::Result<()> {
+        self.conn.execute(
+            "INSERT INTO prices (timestamp, currency, ore, sol)
+            SELECT ?1, ?2, ?3, ?4
+            WHERE NOT EXISTS (
+                SELECT 1 FROM prices
+                WHERE currency = ?2 AND timestamp > ?1 - ?5
+            )",
+            params![
+                point.timestamp,
+                point.currency,
+                point.ore,
+                point.sol,
+                interval
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Stores prices backfilled from a price API.
+    pub fn record_prices(&mut self, points: &[PricePoint]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO prices (timestamp, currency, ore, sol) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for p in points {
+                insert.execute(params![p.timestamp, p.currency, p.ore, p.sol])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Returns the prices in a currency stored since `from`, oldest first.
+    pub fn prices(&self, currency: &str, from: i64) -> rusqlite::Result<Vec<PricePoint>> {
+        let mut select = self.conn.prepare_cached(
+            "SELECT timestamp, currency, ore, sol FROM prices
+            WHERE currency = ?1 AND timestamp >= ?2
+            ORDER BY timestamp",
+        )?;
+        let rows = select.query_map(params![currency, from], |row| {
+            Ok(PricePoint {
+                timestamp: row.get(0)?,
+                currency: row.get(1)?,
+                ore: row.get(2)?,
+                sol: row.get(3)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Deletes snapshots past the retention period and thins out those older than
+    /// the full resolution period to the latest one per downsampling bucket.
+    /// Returns the number of deleted snapshots.
+    pub fn compact(&mut self, retention: &Retention, now: i64) -> rusqlite::Result<usize> {
+        let expired = now - retention.retention_days as i64 * 86400;
+        let coarse = now - retention.full_resolution_days as i64 * 86400;
+        let bucket = (retention.downsample_minutes as i64 * 60).max(1);
+        let tx = self.conn.transaction()?;
+        let mut deleted = tx.execute("DELETE FROM snapshots WHERE timestamp < ?1", [expired])?;
+        tx.execute(
+            "DELETE FROM automation_runs WHERE timestamp < ?1",
+            [expired],
+        )?;
+        tx.execute("DELETE FROM schedule_runs WHERE timestamp < ?1", [expired])?;
+        tx.execute("DELETE FROM prices WHERE timestamp < ?1", [expired])?;
+        // Keep the last expired transition of every miner, which gives its state afterwards
+        deleted += tx.execute(
+            "DELETE FROM transitions
+            WHERE timestamp < ?1 AND rowid NOT IN (
+                SELECT MAX(rowid) FROM transitions
+                WHERE timestamp < ?1
+                GROUP BY authority
+            )",
+            [expired],
+        )?;
+        deleted += tx.execute(
+            "DELETE FROM snapshots
+            WHERE timestamp < ?1 AND rowid NOT IN (
+                SELECT MAX(rowid) FROM snapshots
+                WHERE timestamp < ?1
+                GROUP BY authority, timestamp / ?2
+            )",
+            [coarse, bucket],
+        )?;
+        tx.commit()?;
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86400;
+
+    fn snapshot(authority: Pubkey, timestamp: i64) -> Snapshot {
+        Snapshot {
+            authority,
+            timestamp,
+            balance: 1.0,
+            stake: 2.0,
+            total_hashes: 3,
+            total_rewards: 4,
+            last_hash_at: timestamp,
+            online: true,
+        }
+    }
+
+    fn retention() -> Retention {
+        Retention {
+            full_resolution_days: 2,
+            downsample_minutes: 60,
+            retention_days: 90,
+        }
+    }
+
+    #[test]
+    fn query_returns_the_snapshots_of_a_miner_in_range() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        let (miner, other) = (Pubkey::new_unique(), Pubkey::new_unique());
+        history
+            .record(&[
+                snapshot(miner, 300),
+                snapshot(other, 200),
+                snapshot(miner, 100),
+                snapshot(miner, 900),
+            ])
+            .unwrap();
+        let timestamps: Vec<i64> = history
+            .query(&miner, 0, 500)
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![100, 300]);
+    }
+
+    #[test]
+    fn compact_keeps_the_latest_snapshot_per_bucket_of_old_data() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        let miner = Pubkey::new_unique();
+        let now = 100 * DAY;
+        let old = now - 10 * DAY;
+        let recent = now - DAY;
+        history
+            .record(&[
+                // Three snapshots within one hour past the full resolution period
+                snapshot(miner, old),
+                snapshot(miner, old + 600),
+                snapshot(miner, old + 1200),
+                // Recent snapshots stay as recorded
+                snapshot(miner, recent),
+                snapshot(miner, recent + 600),
+            ])
+            .unwrap();
+        assert_eq!(history.compact(&retention(), now).unwrap(), 2);
+        let timestamps: Vec<i64> = history
+            .query(&miner, 0, now)
+            .unwrap()
+            .iter()
+            .map(|s| s.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![old + 1200, recent, recent + 600]);
+    }
+
+    #[test]
+    fn compact_deletes_snapshots_past_retention() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        let miner = Pubkey::new_unique();
+        let now = 100 * DAY;
+        history
+            .record(&[snapshot(miner, now - 91 * DAY), snapshot(miner, now)])
+            .unwrap();
+        assert_eq!(history.compact(&retention(), now).unwrap(), 1);
+        assert_eq!(history.query(&miner, 0, now).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn only_changes_of_the_online_state_are_recorded() {
+        let history = History::open(Path::new(":memory:")).unwrap();
+        let miner = Pubkey::new_unique();
+        history.record_online(&miner, 100, true).unwrap();
+        history.record_online(&miner, 200, true).unwrap();
+        history.record_online(&miner, 300, false).unwrap();
+        // A transition never predates the previous one
+        history.record_online(&miner, 250, true).unwrap();
+        let transitions: Vec<(i64, bool)> = history
+            .transitions(&miner, 0, 1000)
+            .unwrap()
+            .iter()
+            .map(|t| (t.timestamp, t.online))
+            .collect();
+        assert_eq!(transitions, vec![(100, true), (300, false), (300, true)]);
+    }
+
+    #[test]
+    fn transitions_start_with_the_state_before_the_range() {
+        let history = History::open(Path::new(":memory:")).unwrap();
+        let miner = Pubkey::new_unique();
+        history.record_online(&miner, 100, true).unwrap();
+        history.record_online(&miner, 200, false).unwrap();
+        history.record_online(&miner, 600, true).unwrap();
+        let transitions: Vec<(i64, bool)> = history
+            .transitions(&miner, 500, 1000)
+            .unwrap()
+            .iter()
+            .map(|t| (t.timestamp, t.online))
+            .collect();
+        assert_eq!(transitions, vec![(200, false), (600, true)]);
+    }
+
+    #[test]
+    fn automation_runs_are_read_back_since_a_time() {
+        let history = History::open(Path::new(":memory:")).unwrap();
+        let miner = Pubkey::new_unique();
+        let failed = AutomationRun::failed(
+            miner,
+            200,
+            AutomationAction::Claim,
+            1.5,
+            "Claim failed".to_string(),
+        );
+        history
+            .record_run(&AutomationRun {
+                authority: miner,
+                timestamp: 100,
+                action: AutomationAction::Compound,
+                amount: 2.0,
+                succeeded: true,
+                error: None,
+            })
+            .unwrap();
+        history.record_run(&failed).unwrap();
+        let runs = history.runs(150).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].authority, miner);
+        assert_eq!(runs[0].action, AutomationAction::Claim);
+        assert_eq!(runs[0].amount, 1.5);
+        assert!(!runs[0].succeeded);
+        assert_eq!(runs[0].error.as_deref(), Some("Claim failed"));
+    }
+
+    #[test]
+    fn schedule_runs_are_read_back_newest_first() {
+        let history = History::open(Path::new(":memory:")).unwrap();
+        let miner = Pubkey::new_unique();
+        for (timestamp, error) in [(100, None), (200, Some("Transfer failed".to_string()))] {
+            history
+                .record_schedule_run(&ScheduleRun {
+                    job: "weekly".to_string(),
+                    authority: miner,
+                    timestamp,
+                    action: ScheduledAction::Transfer,
+                    amount: None,
+                    succeeded: error.is_none(),
+                    error,
+                })
+                .unwrap();
+        }
+        let runs = history.schedule_runs(10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].timestamp, 200);
+        assert_eq!(runs[0].error.as_deref(), Some("Transfer failed"));
+        assert!(runs[1].succeeded);
+        assert_eq!(history.schedule_runs(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn prices_are_stored_at_most_once_per_interval() {
+        let history = History::open(Path::new(":memory:")).unwrap();
+        let point = |timestamp: i64, ore: f64| PricePoint {
+            timestamp,
+            currency: "usd".to_string(),
+            ore,
+            sol: Some(150.0),
+        };
+        history.record_price(&point(1000, 1.0), 600).unwrap();
+        history.record_price(&point(1300, 2.0), 600).unwrap();
+        history.record_price(&point(1600, 3.0), 600).unwrap();
+        let prices: Vec<(i64, f64)> = history
+            .prices("usd", 0)
+            .unwrap()
+            .iter()
+            .map(|p| (p.timestamp, p.ore))
+            .collect();
+        assert_eq!(prices, vec![(1000, 1.0), (1600, 3.0)]);
+        assert!(history.prices("eur", 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn backfilled_prices_are_read_back_oldest_first() {
+        let mut history = History::open(Path::new(":memory:")).unwrap();
+        let point = |timestamp: i64| PricePoint {
+            timestamp,
+            currency: "eur".to_string(),
+            ore: 1.0,
+            sol: None,
+        };
+        history
+            .record_prices(&[point(300), point(100), point(200)])
+            .unwrap();
+        let timestamps: Vec<i64> = history
+            .prices("eur", 150)
+            .unwrap()
+            .iter()
+            .map(|p| p.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![200, 300]);
+    }
+
+    #[test]
+    fn missing_retention_fields_take_their_defaults() {
+        let retention: Retention = toml::from_str("retention_days = 30").unwrap();
+        assert_eq!(retention.retention_days, 30);
+        assert_eq!(
+            retention.full_resolution_days,
+            Retention::default().full_resolution_days
+        );
+        assert_eq!(
+            retention.downsample_minutes,
+            Retention::default().downsample_minutes
+        );
+    }
+}
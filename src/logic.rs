@@ -1,63 +1,125 @@
-use crate::balance::MinerStatus;
+use crate::alerts::{deliver_alerts, Alert};
+use crate::automation::{run_rule, AutomationRun};
+use crate::balance::{is_active, FetchError, FetchResult, MinerStatus};
+use crate::batch::fetch_batch;
+use crate::chart::ChartRange;
 use crate::claim::ClaimParams;
+use crate::cluster::{Cluster, Commitment};
+use crate::config;
 use crate::consts::{
-    ACCOUNT_DETAIL_WIDTH, BALANCE_PRECISION, ORE_TOKEN_ID, USD_PRECISION, WINDOW_SIZE,
+    ACCOUNT_DETAIL_WIDTH, ALERT_LIST_SIZE, AUTOMATION_LOG_SECONDS, BALANCE_PRECISION,
+    BALANCE_REQUESTS, EARNINGS_WINDOW_SECONDS, FAILOVER_MAX_FAILURES, FETCH_TIMEOUT_SECONDS,
+    HISTORY_COMPACT_INTERVAL_SECONDS, PRICE_HISTORY_DAYS, PRICE_RECORD_INTERVAL_SECONDS,
+    PRICE_TOLERANCE_SECONDS, REFRESH_GRACE_SECONDS, RETRY_BASE_SECONDS, RETRY_MAX_ATTEMPTS,
+    RPC_MAX_CONCURRENT_REQUESTS, SCHEDULE_LOG_SIZE, WINDOW_SIZE,
 };
-use crate::price::CoinGecko;
+use crate::earnings::Earnings;
+use crate::health::{apply_probe, probe, Probe};
+use crate::history::{PricePoint, Snapshot};
+use crate::portfolio::Prices;
+use crate::price::{backfill_prices, price_gaps, Currency, PriceContext, PriceFeed, PriceQuote};
+use crate::rpc_pool::Endpoint;
+use crate::scheduler::{run_job, ScheduleRun};
 use crate::stake::StakeParams;
-use crate::utils::{is_valid_path, round_dp, save_config};
-use crate::views::dialog_view;
+use crate::transfer::TransferParams;
+use crate::uptime::{availability_report, Uptime, UPTIME_RANGES};
+use crate::utils::{amount_u64_to_f64, is_valid_path, round_dp, save_config};
+use crate::views::{
+    account_detail_view, dialog_view, price_chart_view, restore_config_view, settings_view,
+};
 use crate::{
-    consts::USER_CONFIG_FILE,
-    miner::{Config, Configs, Miner},
+    consts::CONFIG_VERSION,
+    miner::{replace_profile, Config, Configs, Miner, Profile, Settings},
     Dashboard,
 };
-use crate::{Account, ContentType, Dialog};
+use crate::{Account, ContentType, Dialog, SettingsForm};
 use iced::event::Event;
 use iced::widget::{self};
 use iced::{window, Size};
 use iced::{Element, Task, Theme};
 use rfd::FileDialog;
-use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_sdk::commitment_config::CommitmentConfig;
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_request::MAX_MULTIPLE_ACCOUNTS;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
 use solana_sdk::signer::Signer;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
 
 #[derive(Debug, Clone)]
 pub enum TransactionStatus {
     ClaimSucceed,
-    ClaimFailed,
+    ClaimFailed(String),
     StakeSucceed,
-    StakeFailed,
+    StakeFailed(String),
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Refresh,
-    AccountsFetched(Vec<MinerStatus>),
-    BalanceFetched(usize, MinerStatus),
-    PriceFetched(f64),
+    AccountsFetched(u64, Vec<(u64, FetchResult)>),
+    BalanceFetched(u64, u64, FetchResult),
+    BatchFetched(u64, Vec<(u64, FetchResult)>),
+    BalanceRetried(u64, FetchResult),
+    RefreshTimedOut(u64),
+    PriceFetched(Result<PriceQuote, String>),
+    PricesBackfilled(Currency, Result<Vec<PricePoint>, String>),
+    ShowPriceChart,
+    PriceRangeSelected(ChartRange),
+    StatusUpdated(Pubkey, MinerStatus),
+    SubscriptionConnected(Pubkey),
+    SubscriptionFailed(Pubkey),
+    Heartbeat,
     Summary,
+    CheckHealth,
+    HealthProbed(Probe),
+    MoveTargetSelected(String),
+    MoveUnhealthyAccounts,
+    ShowDetail(u64),
+    ChartRangeSelected(ChartRange),
+    ExportAvailability,
+    AlertsDelivered(Vec<String>),
+    ClearAlerts,
+    PauseAutomation(bool),
+    AutomationRan(u64, AutomationRun),
+    SchedulerTick,
+    ScheduledJobRan(ScheduleRun),
     ToggleSubscription(bool),
-    ToggleFetchMode(bool),
-    SetModalView(Option<usize>, fn(&Dashboard) -> Element<Message>),
+    FetchModeSelected(FetchMode),
+    ShowSettings,
+    ProfileSelected(String),
+    NewProfileName(String),
+    NewProfileRpcUrl(String),
+    AddProfile,
+    SettingsInput(SettingField, String),
+    ApplySettings,
+    SetModalView(Option<u64>, fn(&Dashboard) -> Element<Message>),
     ShowModal(ModalType),
     HideModal(Option<Box<Message>>),
     JsonRpcUrl(String),
+    CommitmentSelected(Option<u64>, CommitmentKind, Commitment),
+    FallbackRpcUrls(String),
     Keypair(String),
     PriorityFee(String),
     OpenFile,
     AddAccount,
-    RemoveAccount(usize),
+    RemoveAccount(u64),
     SaveConfig,
+    RestoreConfig(PathBuf),
     ThemeSelected(Theme),
+    CurrencySelected(Currency),
     ClaimAddress(String),
     ClaimAmount(String),
     Claim,
     StakeAmount(String),
     Stake,
+    ProjectionAmount(String),
     EventOccurred(Event),
-    Callback(TransactionStatus),
+    Callback(u64, TransactionStatus),
 }
 
 #[derive(Debug, Clone)]
@@ -66,19 +128,80 @@ pub enum ModalType {
     Sub,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum FetchMode {
     Serial,
     Parallel,
+    Batch,
+    Subscribe,
+}
+
+impl FetchMode {
+    pub const ALL: [FetchMode; 4] = [
+        FetchMode::Serial,
+        FetchMode::Parallel,
+        FetchMode::Batch,
+        FetchMode::Subscribe,
+    ];
+}
+
+impl fmt::Display for FetchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                FetchMode::Serial => "Serial",
+                FetchMode::Parallel => "Parallel",
+                FetchMode::Batch => "Batch",
+                FetchMode::Subscribe => "Subscribe",
+            }
+        )
+    }
+}
+
+/// The fields of the settings form.
+#[derive(Debug, Clone, Copy)]
+pub enum SettingField {
+    DataInterval,
+    SaveInterval,
+    ActivePeriod,
+    JsonRpcUrl,
+    PriorityFee,
+}
+
+/// What a commitment level applies to.
+#[derive(Debug, Clone, Copy)]
+pub enum CommitmentKind {
+    Read,
+    Confirm,
 }
 
 impl Dashboard {
-    /// Load the initial state of the dashboard.
-    pub fn load() -> (Self, Task<Message>) {
+    /// Load the initial state of the dashboard, in the given profile or else the one used last.
+    pub fn load(profile: Option<String>) -> (Self, Task<Message>) {
         println!("Dashboard load..");
-        (
-            Dashboard::init(),
+        let mut dashboard = Dashboard::init(profile);
+        let task = dashboard.start();
+        (dashboard, task)
+    }
+
+    /// Starts the first refresh and health check, offering to restore a backup of a
+    /// config that couldn't be loaded.
+    fn start(&mut self) -> Task<Message> {
+        self.load_prices();
+        let mut tasks = vec![
             Task::perform(async { Message::Refresh }, |msg| msg),
-        )
+            Task::perform(async { Message::CheckHealth }, |msg| msg),
+            self.backfill_prices(),
+        ];
+        if self.config_error.is_some() && !config::backups(&self.config_path).is_empty() {
+            tasks.push(Task::perform(
+                async { Message::SetModalView(None, restore_config_view) },
+                |msg| msg,
+            ));
+        }
+        Task::batch(tasks)
     }
 
     /// Update the state of the dashboard based on the received message.
@@ -86,99 +209,360 @@ impl Dashboard {
         match message {
             Message::Refresh => {
                 println!("Refresh");
+                // Start a new generation, so responses of an earlier refresh are ignored
+                self.generation += 1;
+                self.pending = self.accounts.iter().map(|a| a.id).collect();
                 // Disable refresh button
                 self.is_refreshed = true;
-                match self.fetch_mode {
+                let refresh = match self.fetch_mode {
                     FetchMode::Serial => self.refresh_accounts_serially(),
-                    FetchMode::Parallel => self.refresh_accounts_concurrently(),
-                }
+                    // Subscriptions are seeded and backed up by parallel polling
+                    FetchMode::Parallel | FetchMode::Subscribe => {
+                        self.refresh_accounts_concurrently()
+                    }
+                    FetchMode::Batch => self.refresh_accounts_in_batches(),
+                };
+                // Give up on accounts that are still pending after the deadline
+                let generation = self.generation;
+                let deadline = refresh_deadline(self.fetch_mode, &self.accounts);
+                let watchdog = Task::perform(tokio::time::sleep(deadline), move |_| {
+                    Message::RefreshTimedOut(generation)
+                });
+                Task::batch([refresh, watchdog, self.complete_refresh()])
             }
-            Message::AccountsFetched(accounts_status) => {
+            Message::AccountsFetched(generation, accounts_status) => {
                 #[cfg(debug_assertions)]
                 {
                     println!("Data returned by serial");
                 }
-                // Aggregate account balances and stake amounts
-                let (mut total_balance, mut total_stake, mut active_nodes) = (0.0, 0.0, 0);
-                for (i, s) in accounts_status.into_iter().enumerate() {
-                    if let Some(account) = self.accounts.get_mut(i) {
-                        // Update account status
-                        account.status = s;
-                        account.prepared = true;
-                        // Sum account balances
-                        total_balance += account.status.balance.parse::<f64>().unwrap_or(0.0);
-                        total_stake += account.status.stake.parse::<f64>().unwrap_or(0.0);
-                        // Count active nodes
-                        active_nodes += if account.status.is_online { 1 } else { 0 };
-                    }
-                }
-                // Summarize accounts' data
-                (self.balance, self.stake, self.active_num) =
-                    format_accounts_data(total_balance, total_stake, active_nodes);
-
-                // Enable refresh button
-                self.is_refreshed = false;
-                // Calculate the USD price
-                self.calculate_price()
+                self.receive_fetched(generation, accounts_status)
             }
-            Message::BalanceFetched(index, status) => {
+            Message::BalanceFetched(generation, id, result) => {
                 #[cfg(debug_assertions)]
                 {
                     println!("Data returned by parallel");
                 }
-
-                if let Some(account) = self.accounts.get_mut(index) {
-                    account.status = status;
-                    account.prepared = true;
+                self.receive_fetched(generation, vec![(id, result)])
+            }
+            Message::BatchFetched(generation, accounts_status) => {
+                #[cfg(debug_assertions)]
+                {
+                    println!("Data returned by batch");
                 }
-                // Check if all data has been fetched
-                self.fetch_count += 1;
-                if self.fetch_count == self.accounts.len() {
-                    self.fetch_count = 0;
-                    Task::perform(async { Message::Summary }, |msg| msg)
-                } else {
-                    Task::none()
+                self.receive_fetched(generation, accounts_status)
+            }
+            Message::BalanceRetried(id, result) => {
+                if let Some(account) = self.accounts.iter_mut().find(|a| a.id == id) {
+                    account.retrying = false;
+                }
+                let retry = self.record_fetch(id, result);
+                self.update_summary();
+                retry
+            }
+            Message::RefreshTimedOut(generation) => {
+                if generation != self.generation || self.pending.is_empty() {
+                    return Task::none();
+                }
+                // Report the accounts that never answered as timed out
+                let timed_out: Vec<(u64, FetchResult)> = self
+                    .pending
+                    .iter()
+                    .map(|id| (*id, Err(FetchError::Timeout)))
+                    .collect();
+                self.receive_fetched(generation, timed_out)
+            }
+            Message::StatusUpdated(authority, status) => {
+                for account in self
+                    .accounts
+                    .iter_mut()
+                    .filter(|a| a.status.authority == authority)
+                {
+                    account.apply_result(Ok(status.clone()), self.settings.active_period_seconds);
                 }
+                self.update_summary();
+                Task::none()
+            }
+            Message::SubscriptionConnected(authority) => {
+                self.unsubscribed.remove(&authority);
+                Task::none()
+            }
+            Message::SubscriptionFailed(authority) => {
+                self.unsubscribed.insert(authority);
+                Task::none()
+            }
+            Message::Heartbeat => {
+                // Miners stop sending updates when they go offline, so check the activity here
+                for account in self.accounts.iter_mut().filter(|a| a.status.is_valid) {
+                    account.status.is_online = is_active(
+                        account.status.last_hash_timestamp,
+                        self.settings.active_period_seconds,
+                    );
+                }
+                Task::perform(async { Message::Summary }, |msg| msg)
+            }
+            Message::CheckHealth => {
+                // Forget endpoints no account uses anymore
+                self.health.retain(|url, _| self.rpc_pool.contains(url));
+                Task::batch(self.rpc_pool.endpoints().map(|endpoint| {
+                    Task::perform(probe(Arc::clone(endpoint)), Message::HealthProbed)
+                }))
+            }
+            Message::HealthProbed(probe) => {
+                if self.rpc_pool.contains(&probe.url) {
+                    apply_probe(&mut self.health, probe);
+                    self.review_endpoints();
+                }
+                Task::none()
+            }
+            Message::MoveTargetSelected(url) => {
+                self.move_target = Some(url);
+                Task::none()
+            }
+            Message::MoveUnhealthyAccounts => {
+                let Some(target) = self.move_target.clone() else {
+                    return Task::none();
+                };
+                let ids: Vec<u64> = self
+                    .accounts
+                    .iter()
+                    .filter(|a| {
+                        a.json_rpc_url != target && !self.is_endpoint_healthy(&a.json_rpc_url)
+                    })
+                    .map(|a| a.id)
+                    .collect();
+                for id in ids {
+                    self.move_account(id, &target);
+                }
+                self.retain_endpoints();
+                self.move_target = None;
+                Task::perform(
+                    async { Message::HideModal(Some(Box::new(Message::Refresh))) },
+                    |msg| msg,
+                )
+            }
+            Message::ShowDetail(id) => {
+                self.current_id = Some(id);
+                self.load_snapshots();
+                Task::perform(
+                    async { Message::SetModalView(None, account_detail_view) },
+                    |msg| msg,
+                )
+            }
+            Message::ChartRangeSelected(range) => {
+                self.chart_range = range;
+                self.load_snapshots();
+                Task::none()
             }
             Message::Summary => {
                 // Summarize accounts' data
                 (self.balance, self.stake, self.active_num) = get_accounts_summary(&self.accounts);
                 // Enable refresh button
                 self.is_refreshed = false;
+                self.record_history();
                 // Calculate the USD price
-                self.calculate_price()
+                Task::batch([
+                    self.calculate_price(),
+                    self.evaluate_alerts(),
+                    self.run_automation(),
+                ])
+            }
+            Message::AlertsDelivered(errors) => {
+                for e in errors {
+                    eprintln!("Failed to deliver alert: {}", e);
+                }
+                Task::none()
+            }
+            Message::ClearAlerts => {
+                self.alerts.clear();
+                Task::none()
+            }
+            Message::PauseAutomation(paused) => {
+                self.automation_paused = paused;
+                self.is_saved = false;
+                Task::none()
             }
-            Message::PriceFetched(price) => {
+            Message::AutomationRan(id, run) => {
+                self.automating.remove(&id);
+                self.record_outcome(id, run.succeeded);
+                if let Some(e) = &run.error {
+                    eprintln!("{} failed: {}", run.action, e);
+                }
+                if let Some(history) = &self.history {
+                    if let Err(e) = history.record_run(&run) {
+                        eprintln!("Failed to record automation run: {}", e);
+                    }
+                }
+                self.automation_runs.push(run);
+                Task::none()
+            }
+            Message::SchedulerTick => self.run_due_jobs(),
+            Message::ScheduledJobRan(run) => {
+                if let Some(e) = &run.error {
+                    eprintln!(
+                        "Scheduled job {} failed for {}: {}",
+                        run.job, run.authority, e
+                    );
+                }
+                if let Some(history) = &self.history {
+                    if let Err(e) = history.record_schedule_run(&run) {
+                        eprintln!("Failed to record scheduled run: {}", e);
+                    }
+                }
+                self.schedule_runs.push_front(run);
+                self.schedule_runs.truncate(SCHEDULE_LOG_SIZE);
+                Task::none()
+            }
+            Message::PriceFetched(result) => {
                 #[cfg(debug_assertions)]
                 {
-                    println!("Price has been retrieved");
+                    println!("Price has been retrieved: {:?}", result);
                 }
-                // Update the USD price display
-                if 0.0 == price {
-                    self.stake_usd = "--".to_string();
-                    self.balance_usd = "--".to_string();
-                } else {
-                    self.price_usd = price;
-                    self.stake_usd = self.get_usd(self.stake).to_string();
-                    self.balance_usd = self.get_usd(self.balance).to_string();
+                // Update the value display, keeping the last known price on failure
+                match result {
+                    // Drop quotes requested before the currency was changed
+                    Ok(quote) if quote.currency != self.currency => {}
+                    Ok(quote) => {
+                        self.price = quote.price;
+                        self.sol_price = quote.sol_price.unwrap_or(0.0);
+                        self.record_price(&quote);
+                        self.price_quote = Some(quote);
+                        self.price_error = None;
+                    }
+                    Err(e) => self.price_error = Some(e),
                 }
+                self.stake_value = self.format_value(self.stake);
+                self.balance_value = self.format_value(self.balance);
+                Task::none()
+            }
+            Message::PricesBackfilled(currency, result) => {
+                let points = match result {
+                    Ok(points) => points,
+                    Err(e) => {
+                        eprintln!("Failed to backfill prices: {}", e);
+                        return Task::none();
+                    }
+                };
+                // Only fill in the ranges no stored price covers
+                let now = chrono::Utc::now().timestamp();
+                let from = now - PRICE_HISTORY_DAYS as i64 * 86400;
+                let gaps = price_gaps(&self.price_history, from, now);
+                let in_gap = |p: &PricePoint| {
+                    gaps.iter()
+                        .any(|(start, end)| p.timestamp > *start && p.timestamp < *end)
+                };
+                let points: Vec<PricePoint> = points.into_iter().filter(in_gap).collect();
+                if let Some(history) = &mut self.history {
+                    if let Err(e) = history.record_prices(&points) {
+                        eprintln!("Failed to record prices: {}", e);
+                    }
+                }
+                if currency == self.currency {
+                    self.load_prices();
+                }
+                Task::none()
+            }
+            Message::ShowPriceChart => {
+                self.load_prices();
+                Task::perform(
+                    async { Message::SetModalView(None, price_chart_view) },
+                    |msg| msg,
+                )
+            }
+            Message::PriceRangeSelected(range) => {
+                self.price_range = range;
                 Task::none()
             }
             Message::ToggleSubscription(is_subscribed) => {
                 self.auto_refresh = is_subscribed;
+                self.is_saved = false;
+                Task::none()
+            }
+            Message::FetchModeSelected(fetch_mode) => {
+                self.fetch_mode = fetch_mode;
+                self.unsubscribed.clear();
+                self.is_saved = false;
                 Task::none()
             }
-            Message::ToggleFetchMode(is_parallel) => {
-                self.fetch_mode = if is_parallel {
-                    FetchMode::Parallel
+            Message::ShowSettings => {
+                self.settings_form = SettingsForm {
+                    data_interval: self.data_interval.to_string(),
+                    save_interval: self.save_interval.to_string(),
+                    active_period: self.settings.active_period_seconds.to_string(),
+                    json_rpc_url: self.settings.json_rpc_url.clone(),
+                    priority_fee: self.settings.priority_fee.to_string(),
+                    error: None,
+                };
+                Task::perform(
+                    async { Message::SetModalView(None, settings_view) },
+                    |msg| msg,
+                )
+            }
+            Message::ProfileSelected(name) => {
+                if name == self.profile {
+                    return Task::none();
+                }
+                self.switch_profile(name)
+            }
+            Message::NewProfileName(name) => {
+                self.new_profile = name;
+                Task::none()
+            }
+            Message::NewProfileRpcUrl(url) => {
+                self.new_profile_rpc_url = url;
+                Task::none()
+            }
+            Message::AddProfile => {
+                let name = self.new_profile.trim().to_string();
+                let json_rpc_url = self.new_profile_rpc_url.trim().to_string();
+                let error = if name.is_empty() {
+                    Some("Enter a profile name".to_string())
+                } else if self.profiles.iter().any(|p| p.name == name) {
+                    Some(format!("A profile named {} already exists", name))
+                } else if Url::parse(&json_rpc_url).is_err() {
+                    Some(format!("{} is not a valid url", json_rpc_url))
                 } else {
-                    FetchMode::Serial
+                    None
+                };
+                if let Some(content) = error {
+                    self.dialog = Dialog {
+                        content,
+                        content_type: ContentType::Error,
+                    };
+                    return Task::perform(
+                        async { Message::SetModalView(None, dialog_view) },
+                        |msg| msg,
+                    );
+                }
+                let settings = Settings {
+                    json_rpc_url,
+                    ..Settings::default()
                 };
+                self.profiles.push(Profile::new(&name, settings));
+                self.switch_profile(name)
+            }
+            Message::SettingsInput(field, value) => {
+                let form = &mut self.settings_form;
+                match field {
+                    SettingField::DataInterval => form.data_interval = value,
+                    SettingField::SaveInterval => form.save_interval = value,
+                    SettingField::ActivePeriod => form.active_period = value,
+                    SettingField::JsonRpcUrl => form.json_rpc_url = value,
+                    SettingField::PriorityFee => form.priority_fee = value,
+                }
+                form.error = None;
                 Task::none()
             }
-            Message::SetModalView(index, modal_view) => {
-                if let Some(index) = index {
-                    self.current_index = Some(index);
+            Message::ApplySettings => {
+                if let Err(e) = self.apply_settings() {
+                    self.settings_form.error = Some(e);
+                    return Task::none();
+                }
+                self.is_saved = false;
+                Task::perform(async { Message::HideModal(None) }, |msg| msg)
+            }
+            Message::SetModalView(id, modal_view) => {
+                if let Some(id) = id {
+                    self.current_id = Some(id);
                 }
                 self.modal_view = modal_view;
                 self.show_modal = ModalType::Sub;
@@ -203,6 +587,31 @@ impl Dashboard {
                 self.json_rpc_url = url;
                 Task::none()
             }
+            Message::CommitmentSelected(None, kind, commitment) => {
+                // Commitment of the account being added
+                match kind {
+                    CommitmentKind::Read => self.read_commitment = commitment,
+                    CommitmentKind::Confirm => self.confirm_commitment = commitment,
+                }
+                Task::none()
+            }
+            Message::CommitmentSelected(Some(id), kind, commitment) => {
+                let Some(position) = self.accounts.iter().position(|a| a.id == id) else {
+                    return Task::none();
+                };
+                self.accounts[position].set_commitment(kind, commitment);
+                let config = &mut self.configs[position];
+                match kind {
+                    CommitmentKind::Read => config.read_commitment = commitment,
+                    CommitmentKind::Confirm => config.confirm_commitment = commitment,
+                }
+                self.is_saved = false;
+                Task::none()
+            }
+            Message::FallbackRpcUrls(urls) => {
+                self.fallback_rpc_urls = urls;
+                Task::none()
+            }
             Message::Keypair(keypair) => {
                 self.keypair = keypair;
                 Task::none()
@@ -236,22 +645,22 @@ impl Dashboard {
                         None
                     },
                 };
-                // Get account from current index
-                if let Some(account) = self
-                    .accounts
-                    .get(self.current_index.expect("No account selected"))
-                {
-                    // Reset current index
-                    self.current_index = None;
+                // Get the selected account
+                if let Some(account) = self.account(self.current_id.expect("No account selected")) {
+                    let id = account.id;
                     let miner = Arc::clone(&account.miner);
+                    // Reset the selected account
+                    self.current_id = None;
                     println!("pubkey:{:?}", miner.signer().pubkey());
-                    Task::perform(request_claim(miner, params), |msg| {
-                        let transaction_status = if msg {
-                            TransactionStatus::ClaimSucceed
-                        } else {
-                            TransactionStatus::ClaimFailed
+                    Task::perform(request_claim(miner, params), move |result| {
+                        let transaction_status = match result {
+                            Ok(_) => TransactionStatus::ClaimSucceed,
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                TransactionStatus::ClaimFailed(e)
+                            }
                         };
-                        Message::Callback(transaction_status)
+                        Message::Callback(id, transaction_status)
                     })
                 } else {
                     Task::none()
@@ -274,26 +683,35 @@ impl Dashboard {
                     // Currently, only self-staking is allowed
                     sender: None,
                 };
-                // Get account from current index
-                if let Some(account) = self
-                    .accounts
-                    .get(self.current_index.expect("No account selected"))
-                {
-                    self.current_index = None;
+                // Get the selected account
+                if let Some(account) = self.account(self.current_id.expect("No account selected")) {
+                    let id = account.id;
                     let miner = Arc::clone(&account.miner);
-                    Task::perform(request_stake(miner, params), |msg| {
-                        let transaction_status = if msg {
-                            TransactionStatus::StakeSucceed
-                        } else {
-                            TransactionStatus::StakeFailed
+                    self.current_id = None;
+                    Task::perform(request_stake(miner, params), move |result| {
+                        let transaction_status = match result {
+                            Ok(_) => TransactionStatus::StakeSucceed,
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                TransactionStatus::StakeFailed(e)
+                            }
                         };
-                        Message::Callback(transaction_status)
+                        Message::Callback(id, transaction_status)
                     })
                 } else {
                     Task::none()
                 }
             }
-            Message::Callback(status) => {
+            Message::ProjectionAmount(amount) => {
+                self.projection_amount = amount;
+                Task::none()
+            }
+            Message::Callback(id, status) => {
+                let failed = matches!(
+                    status,
+                    TransactionStatus::ClaimFailed(_) | TransactionStatus::StakeFailed(_)
+                );
+                self.record_outcome(id, !failed);
                 // Reset stake amount
                 self.stake_amount = String::default();
                 // Reset claim amount
@@ -308,16 +726,16 @@ impl Dashboard {
                         content: "Congratulation! Claim succeeded".to_string(),
                         content_type: ContentType::Good,
                     },
-                    TransactionStatus::ClaimFailed => Dialog {
-                        content: "Claim failed!".to_string(),
+                    TransactionStatus::ClaimFailed(e) => Dialog {
+                        content: format!("Claim failed! {}", e),
                         content_type: ContentType::Error,
                     },
                     TransactionStatus::StakeSucceed => Dialog {
                         content: "Congratulation! Stake succeeded".to_string(),
                         content_type: ContentType::Good,
                     },
-                    TransactionStatus::StakeFailed => Dialog {
-                        content: "Stake failed!".to_string(),
+                    TransactionStatus::StakeFailed(e) => Dialog {
+                        content: format!("Stake failed! {}", e),
                         content_type: ContentType::Error,
                     },
                 };
@@ -325,6 +743,31 @@ impl Dashboard {
                     msg
                 })
             }
+            Message::ExportAvailability => {
+                let now = chrono::Utc::now().timestamp();
+                let miners: Vec<(String, Vec<Uptime>)> = self
+                    .accounts
+                    .iter()
+                    .filter(|a| a.status.is_valid)
+                    .map(|a| {
+                        let uptimes = UPTIME_RANGES
+                            .iter()
+                            .map(|range| self.get_uptime(&a.status.authority, *range, now))
+                            .collect();
+                        (a.status.authority.to_string(), uptimes)
+                    })
+                    .collect();
+                if let Some(path) = FileDialog::new()
+                    .set_title("Save the availability report...")
+                    .set_file_name("availability.csv")
+                    .save_file()
+                {
+                    if let Err(e) = std::fs::write(&path, availability_report(&miners)) {
+                        eprintln!("Failed to save availability report: {}", e);
+                    }
+                }
+                Task::none()
+            }
             Message::OpenFile => {
                 if let Some(path) = FileDialog::new()
                     .set_title("Open a keypair file...")
@@ -346,19 +789,32 @@ impl Dashboard {
                     );
                 }
 
+                let fallback_rpc_urls: Vec<String> = self
+                    .fallback_rpc_urls
+                    .split(',')
+                    .map(|url| url.trim().to_string())
+                    .filter(|url| !url.is_empty())
+                    .collect();
+                let config = Config {
+                    json_rpc_url: self.json_rpc_url.clone(),
+                    fallback_rpc_urls,
+                    keypair_path: self.keypair.clone(),
+                    priority_fee: self
+                        .priority_fee
+                        .parse::<u64>()
+                        .unwrap_or(self.settings.priority_fee),
+                    read_commitment: self.read_commitment,
+                    confirm_commitment: self.confirm_commitment,
+                    automation: vec![],
+                };
                 let account = create_account(
-                    self.json_rpc_url.clone(),
-                    self.keypair.clone(),
-                    self.priority_fee.parse::<u64>().unwrap_or(10u64),
+                    self.next_account_id(),
+                    self.rpc_pool.get(&self.json_rpc_url),
+                    &config,
                 );
                 self.accounts.push(account);
 
                 // Update user's configs
-                let config = Config {
-                    json_rpc_url: self.json_rpc_url.clone(),
-                    keypair_path: self.keypair.clone(),
-                    priority_fee: self.priority_fee.parse::<u64>().unwrap_or(0 as u64),
-                };
                 self.configs.push(config);
                 self.is_saved = false;
 
@@ -367,40 +823,81 @@ impl Dashboard {
                     |msg| msg,
                 )
             }
-            Message::RemoveAccount(index) => {
-                self.current_index = None;
-                // Before removing an account, decrease the active_num
-                let account = self.accounts.get(index).unwrap();
-                if account.status.is_online {
-                    self.active_num -= 1;
+            Message::RemoveAccount(id) => {
+                self.current_id = None;
+                if let Some(position) = self.accounts.iter().position(|a| a.id == id) {
+                    // Remove an account
+                    self.accounts.remove(position);
+                    self.alert_engine.forget(id);
+                    self.automating.remove(&id);
+                    // Update user's configs
+                    self.configs.remove(position);
+                    self.is_saved = false;
+                    self.retain_endpoints();
                 }
-                // Remove an account
-                self.accounts.remove(index);
-                // Update user's configs
-                self.configs.remove(index);
-                self.is_saved = false;
+                // Don't wait for the removed account to finish the current refresh
+                self.pending.remove(&id);
+                self.update_summary();
 
-                Task::perform(async { Message::HideModal(None) }, |msg| msg)
+                Task::batch([
+                    self.complete_refresh(),
+                    Task::perform(async { Message::HideModal(None) }, |msg| msg),
+                ])
             }
             Message::SaveConfig => {
                 save_user_config(self);
                 Task::none()
             }
+            Message::RestoreConfig(backup) => {
+                if let Err(e) = config::restore_backup(&self.config_path, &backup) {
+                    eprintln!("Failed to restore {}: {}", backup.display(), e);
+                    self.dialog = Dialog {
+                        content: "Failed to restore the backup".to_string(),
+                        content_type: ContentType::Error,
+                    };
+                    return Task::perform(
+                        async { Message::SetModalView(None, dialog_view) },
+                        |msg| msg,
+                    );
+                }
+                // Start over from the restored config
+                *self = Dashboard::init(config::cli_profile());
+                self.start()
+            }
             Message::ThemeSelected(theme) => {
                 self.theme = theme;
                 self.is_saved = false;
                 Task::none()
             }
+            Message::CurrencySelected(currency) => {
+                if currency == self.currency {
+                    return Task::none();
+                }
+                // Values in the old currency are meaningless until the new price arrives
+                self.currency = currency;
+                self.price = 0.0;
+                self.sol_price = 0.0;
+                self.price_quote = None;
+                self.stake_value = self.format_value(self.stake);
+                self.balance_value = self.format_value(self.balance);
+                self.is_saved = false;
+                self.load_prices();
+                Task::batch([self.calculate_price(), self.backfill_prices()])
+            }
             Message::EventOccurred(event) => {
                 match event {
-                    Event::Window(window::Event::Resized(Size { width, height: _height })) => {
+                    Event::Window(window::Event::Resized(Size {
+                        width,
+                        height: _height,
+                    })) => {
                         // Calculate the number of items in each row
                         self.extend_items_per_row =
                             ((width as f32 - WINDOW_SIZE.0) / ACCOUNT_DETAIL_WIDTH as f32) as u8;
 
                         #[cfg(debug_assertions)]
                         {
-                            let number_f = (width as f32 - WINDOW_SIZE.0) / ACCOUNT_DETAIL_WIDTH as f32;
+                            let number_f =
+                                (width as f32 - WINDOW_SIZE.0) / ACCOUNT_DETAIL_WIDTH as f32;
                             println!(
                                 "width: {:?} height: {:?} items: {:?}/{:?}",
                                 width, _height, number_f, self.extend_items_per_row
@@ -420,48 +917,714 @@ impl Dashboard {
         }
     }
 
+    /// Returns the account with the given id.
+    pub fn account(&self, id: u64) -> Option<&Account> {
+        self.accounts.iter().find(|a| a.id == id)
+    }
+
+    /// Hands out a new id, which stays with an account however the list changes.
+    pub fn next_account_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// Checks whether the endpoint of a URL is healthy. Endpoints not probed yet count as healthy.
+    pub fn is_endpoint_healthy(&self, url: &str) -> bool {
+        self.health.get(url).map_or(true, |h| h.is_healthy())
+    }
+
+    /// The cluster an account reads from, told by the genesis hash of its endpoint once
+    /// probed, else by the URL.
+    pub fn cluster_of(&self, account: &Account) -> Cluster {
+        let genesis_hash = self
+            .health
+            .get(&account.json_rpc_url)
+            .and_then(|h| h.genesis_hash.as_ref());
+        Cluster::detect(&account.json_rpc_url, genesis_hash)
+    }
+
+    /// The known clusters the accounts of the profile point at, when there are several.
+    /// Endpoints of unknown clusters can't be compared, so they are left out.
+    pub fn mixed_clusters(&self) -> Vec<Cluster> {
+        let mut clusters = vec![];
+        for cluster in self.accounts.iter().map(|a| self.cluster_of(a)) {
+            if cluster != Cluster::Custom && !clusters.contains(&cluster) {
+                clusters.push(cluster);
+            }
+        }
+        if clusters.len() > 1 {
+            clusters
+        } else {
+            vec![]
+        }
+    }
+
+    /// Moves an account to another primary RPC endpoint, keeping its id and last known status.
+    pub fn move_account(&mut self, id: u64, url: &str) {
+        let endpoint = self.rpc_pool.get(url);
+        let Some(position) = self.accounts.iter().position(|a| a.id == id) else {
+            return;
+        };
+        let account = &mut self.accounts[position];
+        account.set_endpoint(endpoint);
+        account.rpc_urls[0] = url.to_string();
+        account.rpc_index = 0;
+        // Update user's configs
+        self.configs[position].json_rpc_url = url.to_string();
+        self.is_saved = false;
+    }
+
+    /// Counts consecutive failed requests of an account and switches it to a fallback
+    /// endpoint once they pile up.
+    fn record_outcome(&mut self, id: u64, succeeded: bool) {
+        let Some(account) = self.accounts.iter_mut().find(|a| a.id == id) else {
+            return;
+        };
+        if succeeded {
+            account.failures = 0;
+            return;
+        }
+        account.failures += 1;
+        if account.failures >= FAILOVER_MAX_FAILURES {
+            self.fail_over(id);
+        }
+    }
+
+    /// Switches an account to the next of its endpoints that is not known to be unhealthy.
+    pub fn fail_over(&mut self, id: u64) {
+        let Some(account) = self.account(id) else {
+            return;
+        };
+        if let Some(index) = account.next_endpoint(|url| self.is_endpoint_healthy(url)) {
+            self.switch_endpoint(id, index);
+        }
+    }
+
+    /// Points an account at one of its endpoints, without touching the user's configs.
+    fn switch_endpoint(&mut self, id: u64, index: usize) {
+        let Some(account) = self.accounts.iter_mut().find(|a| a.id == id) else {
+            return;
+        };
+        let endpoint = self.rpc_pool.get(&account.rpc_urls[index]);
+        #[cfg(debug_assertions)]
+        {
+            println!("Account {} switched to {}", id, endpoint.url);
+        }
+        account.set_endpoint(endpoint);
+        account.rpc_index = index;
+        self.retain_endpoints();
+    }
+
+    /// Fails accounts over from unhealthy endpoints and back to their recovered primary ones.
+    pub fn review_endpoints(&mut self) {
+        let mut recovered = vec![];
+        let mut unhealthy = vec![];
+        for a in &self.accounts {
+            let primary = self.health.get(&a.rpc_urls[0]);
+            if a.rpc_index != 0 && primary.is_some_and(|h| h.is_recovered()) {
+                recovered.push(a.id);
+            } else if a.rpc_urls.len() > 1 && !self.is_endpoint_healthy(&a.json_rpc_url) {
+                unhealthy.push(a.id);
+            }
+        }
+        for id in recovered {
+            self.switch_endpoint(id, 0);
+        }
+        for id in unhealthy {
+            self.fail_over(id);
+        }
+    }
+
+    /// Drops the endpoints no account uses, but keeps the primary endpoints
+    /// of failed over accounts, so their recovery is noticed.
+    pub fn retain_endpoints(&mut self) {
+        self.rpc_pool.retain_used(
+            self.accounts
+                .iter()
+                .flat_map(|a| [a.json_rpc_url.as_str(), a.rpc_urls[0].as_str()]),
+        );
+    }
+
+    /// Records the results of a refresh, ignoring those of superseded refreshes
+    /// and of accounts that are no longer pending.
+    pub fn receive_fetched(
+        &mut self,
+        generation: u64,
+        results: Vec<(u64, FetchResult)>,
+    ) -> Task<Message> {
+        if generation != self.generation {
+            return Task::none();
+        }
+        let mut commands = vec![];
+        for (id, result) in results {
+            if self.pending.remove(&id) {
+                commands.push(self.record_fetch(id, result));
+            }
+        }
+        commands.push(self.complete_refresh());
+        Task::batch(commands)
+    }
+
+    /// Summarizes accounts' data once no account of the current refresh is pending.
+    pub fn complete_refresh(&mut self) -> Task<Message> {
+        if self.is_refreshed && self.pending.is_empty() {
+            self.is_refreshed = false;
+            Task::perform(async { Message::Summary }, |msg| msg)
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Saves the profile in use and starts over in another one.
+    fn switch_profile(&mut self, name: String) -> Task<Message> {
+        save_user_config(self);
+        let generation = self.generation;
+        *self = Dashboard::init(Some(name));
+        // Ignore responses to refreshes of the previous profile
+        self.generation = generation;
+        // Start in this profile next time
+        self.is_saved = false;
+        self.start()
+    }
+
+    /// Validates the settings form and applies it, judging again which miners are online.
+    fn apply_settings(&mut self) -> Result<(), String> {
+        let settings = parse_settings(&self.settings_form, &self.settings)?;
+        self.data_interval = settings.data_interval;
+        self.save_interval = settings.save_interval;
+        // Suggest the new defaults in the add account form
+        self.json_rpc_url = settings.json_rpc_url.clone();
+        self.priority_fee = settings.priority_fee.to_string();
+        for account in self.accounts.iter_mut().filter(|a| a.status.is_valid) {
+            account.status.is_online = is_active(
+                account.status.last_hash_timestamp,
+                settings.active_period_seconds,
+            );
+        }
+        self.settings = settings;
+        self.update_summary();
+        Ok(())
+    }
+
+    /// Records the fetch result of an account and retries transient failures
+    /// with exponential backoff, instead of waiting for the next refresh.
+    pub fn record_fetch(&mut self, id: u64, result: FetchResult) -> Task<Message> {
+        let Some(account) = self.accounts.iter_mut().find(|a| a.id == id) else {
+            return Task::none();
+        };
+        account.apply_result(result, self.settings.active_period_seconds);
+        // Count the outcome towards the error rate of the endpoint
+        let failed = account.error.as_ref().is_some_and(|e| e.is_retryable());
+        self.health
+            .entry(account.json_rpc_url.clone())
+            .or_default()
+            .record(failed);
+        // Move on to a fallback endpoint when the one in use keeps failing
+        self.record_outcome(id, !failed);
+        let Some(account) = self.accounts.iter_mut().find(|a| a.id == id) else {
+            return Task::none();
+        };
+        match &account.error {
+            Some(e)
+                if e.is_retryable()
+                    && !account.retrying
+                    && account.retries <= RETRY_MAX_ATTEMPTS =>
+            {
+                account.retrying = true;
+                let delay = retry_delay(account.retries);
+                let (endpoint, miner) = (Arc::clone(&account.endpoint), Arc::clone(&account.miner));
+                Task::perform(retry_balance(endpoint, miner, delay), move |result| {
+                    Message::BalanceRetried(id, result)
+                })
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Stores a snapshot of every account with an up to date status,
+    /// compacting the history once in a while.
+    pub fn record_history(&mut self) {
+        let Some(history) = self.history.as_mut() else {
+            return;
+        };
+        let now = chrono::Utc::now().timestamp();
+        let snapshots: Vec<Snapshot> = self
+            .accounts
+            .iter()
+            .filter(|a| a.prepared && a.status.is_valid && a.error.is_none())
+            .map(|a| Snapshot::from_status(&a.status, now))
+            .collect();
+        if let Err(e) = history.record(&snapshots) {
+            eprintln!("Failed to record history: {}", e);
+        }
+        // Record when miners went offline or came back
+        for s in &snapshots {
+            let changed_at = if s.online {
+                s.last_hash_at
+            } else {
+                s.last_hash_at + self.settings.active_period_seconds
+            };
+            if let Err(e) = history.record_online(&s.authority, changed_at.min(now), s.online) {
+                eprintln!("Failed to record online state: {}", e);
+            }
+        }
+        // Estimate the earnings of every miner from its recent history
+        for a in self.accounts.iter_mut() {
+            a.earnings = history
+                .query(&a.status.authority, now - EARNINGS_WINDOW_SECONDS, now)
+                .ok()
+                .and_then(|snapshots| Earnings::from_snapshots(&snapshots));
+        }
+        self.earnings = get_fleet_earnings(&self.accounts);
+        if now - self.last_compacted >= HISTORY_COMPACT_INTERVAL_SECONDS {
+            self.last_compacted = now;
+            match history.compact(&self.retention, now) {
+                Ok(_deleted) => {
+                    #[cfg(debug_assertions)]
+                    {
+                        println!("History compacted, {} snapshots deleted", _deleted);
+                    }
+                }
+                Err(e) => eprintln!("Failed to compact history: {}", e),
+            }
+        }
+        // Keep the charts of the selected account up to date
+        if self.current_id.is_some() {
+            self.load_snapshots();
+        }
+    }
+
+    /// Checks the alert rules against every account, listing the alerts that fire
+    /// and delivering them to the configured sinks.
+    pub fn evaluate_alerts(&mut self) -> Task<Message> {
+        let now = chrono::Utc::now().timestamp();
+        let unhealthy: HashSet<&str> = self
+            .accounts
+            .iter()
+            .map(|a| a.json_rpc_url.as_str())
+            .filter(|url| !self.is_endpoint_healthy(url))
+            .collect();
+        let fired: Vec<Alert> = self.alert_engine.evaluate(
+            &self.alerts_config.rules,
+            &self.accounts,
+            |url| !unhealthy.contains(url),
+            now,
+        );
+        if fired.is_empty() {
+            return Task::none();
+        }
+        for alert in &fired {
+            self.alerts.push_front(alert.clone());
+        }
+        self.alerts.truncate(ALERT_LIST_SIZE);
+        if self.alert_sinks.is_empty() {
+            return Task::none();
+        }
+        let sinks = self.alert_sinks.clone();
+        Task::perform(deliver_alerts(sinks, fired), Message::AlertsDelivered)
+    }
+
+    /// Runs the automation rules of every account with an up to date status,
+    /// unless automation is paused or an earlier run of the account is still in flight.
+    pub fn run_automation(&mut self) -> Task<Message> {
+        let now = chrono::Utc::now().timestamp();
+        self.automation_runs
+            .retain(|r| now - r.timestamp < AUTOMATION_LOG_SECONDS);
+        if self.automation_paused {
+            return Task::none();
+        }
+        let mut commands = vec![];
+        for (account, config) in self.accounts.iter().zip(&self.configs) {
+            if !account.prepared
+                || !account.status.is_valid
+                || account.error.is_some()
+                || self.automating.contains(&account.id)
+            {
+                continue;
+            }
+            // Rules of an account run one at a time, as they spend the same wallet
+            let planned = config.automation.iter().find_map(|rule| {
+                rule.plan(&account.status, &self.automation_runs, now)
+                    .map(|amount| (rule.clone(), amount))
+            });
+            if let Some((rule, amount)) = planned {
+                let id = account.id;
+                let (miner, authority) = (Arc::clone(&account.miner), account.status.authority);
+                self.automating.insert(id);
+                commands.push(Task::perform(
+                    run_rule(miner, rule, authority, amount, now),
+                    move |run| Message::AutomationRan(id, run),
+                ));
+            }
+        }
+        Task::batch(commands)
+    }
+
+    /// Runs every due job for the miners it targets, then schedules its next run.
+    pub fn run_due_jobs(&mut self) -> Task<Message> {
+        let now = chrono::Utc::now().timestamp();
+        let mut commands = vec![];
+        for job in self.jobs.iter_mut().filter(|j| j.is_due(now)) {
+            let scheduled = job.next_run.unwrap_or(now);
+            for a in &self.accounts {
+                let authority = match a.miner.try_signer() {
+                    Ok(signer) => signer.pubkey(),
+                    Err(e) => {
+                        // Log the run as failed under the last known authority of the miner
+                        let authority = a.status.authority;
+                        if job.targets(&authority) {
+                            let run = ScheduleRun::failed(&job.config, authority, scheduled, e);
+                            commands
+                                .push(Task::perform(async move { run }, Message::ScheduledJobRan));
+                        }
+                        continue;
+                    }
+                };
+                if job.targets(&authority) {
+                    let (miner, config) = (Arc::clone(&a.miner), job.config.clone());
+                    commands.push(Task::perform(
+                        run_job(miner, config, authority, scheduled),
+                        Message::ScheduledJobRan,
+                    ));
+                }
+            }
+            job.advance(now);
+        }
+        Task::batch(commands)
+    }
+
+    /// Loads the history and uptime of the selected account.
+    pub fn load_snapshots(&mut self) {
+        self.snapshots.clear();
+        self.uptime.clear();
+        self.timeline = Uptime::default();
+        let Some(authority) = self
+            .current_id
+            .and_then(|id| self.account(id))
+            .map(|a| a.status.authority)
+        else {
+            return;
+        };
+        let now = chrono::Utc::now().timestamp();
+        if let Some(history) = &self.history {
+            match history.query(&authority, now - self.chart_range.seconds(), now) {
+                Ok(snapshots) => self.snapshots = snapshots,
+                Err(e) => eprintln!("Failed to query history: {}", e),
+            }
+        }
+        self.uptime = UPTIME_RANGES
+            .iter()
+            .map(|range| self.get_uptime(&authority, *range, now))
+            .collect();
+        self.timeline = self.get_uptime(&authority, self.chart_range, now);
+    }
+
+    /// Loads the stored prices of the last days in the display currency.
+    pub fn load_prices(&mut self) {
+        self.price_history.clear();
+        let from = chrono::Utc::now().timestamp() - PRICE_HISTORY_DAYS as i64 * 86400;
+        if let Some(history) = &self.history {
+            match history.prices(self.currency.code(), from) {
+                Ok(points) => self.price_history = points,
+                Err(e) => eprintln!("Failed to query prices: {}", e),
+            }
+        }
+    }
+
+    /// Backfills the prices of the last days from the market chart where stored prices
+    /// are missing, e.g. while ORE-BAR was closed.
+    pub fn backfill_prices(&self) -> Task<Message> {
+        let now = chrono::Utc::now().timestamp();
+        let from = now - PRICE_HISTORY_DAYS as i64 * 86400;
+        if price_gaps(&self.price_history, from, now).is_empty() || self.history.is_none() {
+            return Task::none();
+        }
+        let currency = self.currency.clone();
+        Task::perform(
+            backfill_prices(currency.clone(), PRICE_HISTORY_DAYS),
+            move |result| Message::PricesBackfilled(currency.clone(), result),
+        )
+    }
+
+    /// Stores a price quote, sampled at most once per record interval.
+    fn record_price(&mut self, quote: &PriceQuote) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let point = PricePoint {
+            timestamp: quote.timestamp,
+            currency: quote.currency.code().to_string(),
+            ore: quote.price,
+            sol: quote.sol_price,
+        };
+        if let Err(e) = history.record_price(&point, PRICE_RECORD_INTERVAL_SECONDS) {
+            eprintln!("Failed to record price: {}", e);
+        }
+        let sampled = self.price_history.last().map_or(true, |p| {
+            point.timestamp - p.timestamp >= PRICE_RECORD_INTERVAL_SECONDS
+        });
+        if sampled {
+            self.price_history.push(point);
+        }
+    }
+
+    /// The latest stored prices at a moment, if stored shortly before it.
+    pub fn price_at(&self, timestamp: i64) -> Option<&PricePoint> {
+        let position = self
+            .price_history
+            .partition_point(|p| p.timestamp <= timestamp);
+        self.price_history[..position]
+            .last()
+            .filter(|p| timestamp - p.timestamp <= PRICE_TOLERANCE_SECONDS)
+    }
+
+    /// The value of an amount of ORE at the price of a past moment.
+    pub fn value_at(&self, amount: f64, timestamp: i64) -> Option<f64> {
+        self.price_at(timestamp).map(|p| amount * p.ore)
+    }
+
+    /// Values the rewards earned between consecutive snapshots at the price of the
+    /// moment they were seen. Rewards without a stored price are left out of the value.
+    pub fn rewards_value(&self, snapshots: &[Snapshot]) -> (f64, f64) {
+        let (mut rewards, mut value) = (0.0, 0.0);
+        for pair in snapshots.windows(2) {
+            let earned =
+                amount_u64_to_f64(pair[1].total_rewards.saturating_sub(pair[0].total_rewards));
+            rewards += earned;
+            value += self.value_at(earned, pair[1].timestamp).unwrap_or(0.0);
+        }
+        (rewards, value)
+    }
+
+    /// The current prices of the assets, or `None` while the ORE price is unknown.
+    pub fn prices(&self) -> Option<Prices> {
+        (0.0 != self.price).then(|| Prices {
+            ore: self.price,
+            sol: (0.0 != self.sol_price).then_some(self.sol_price),
+        })
+    }
+
+    /// Computes the uptime of a miner over a range ending now.
+    pub fn get_uptime(&self, authority: &Pubkey, range: ChartRange, now: i64) -> Uptime {
+        let from = now - range.seconds();
+        let transitions = match &self.history {
+            Some(history) => history
+                .transitions(authority, from, now)
+                .unwrap_or_else(|e| {
+                    eprintln!("Failed to query transitions: {}", e);
+                    vec![]
+                }),
+            None => vec![],
+        };
+        Uptime::from_transitions(&transitions, from, now)
+    }
+
+    /// Summarizes accounts' data with the latest known price.
+    pub fn update_summary(&mut self) {
+        (self.balance, self.stake, self.active_num) = get_accounts_summary(&self.accounts);
+        self.earnings = get_fleet_earnings(&self.accounts);
+        if 0.0 != self.price {
+            self.stake_value = self.format_value(self.stake);
+            self.balance_value = self.format_value(self.balance);
+        }
+    }
+
     pub fn refresh_accounts_serially(&self) -> Task<Message> {
         let mut miners = vec![];
         for a in &self.accounts {
-            miners.push(Arc::clone(&a.miner));
+            miners.push((a.id, Arc::clone(&a.endpoint), Arc::clone(&a.miner)));
         }
-        Task::perform(fetch_accounts_balance(miners), Message::AccountsFetched)
+        let generation = self.generation;
+        Task::perform(fetch_accounts_balance(miners), move |results| {
+            Message::AccountsFetched(generation, results)
+        })
     }
 
     pub fn refresh_accounts_concurrently(&self) -> Task<Message> {
         let mut commands = vec![];
-        for (i, a) in self.accounts.iter().enumerate() {
-            let miner = Arc::clone(&a.miner);
-            commands.push(Task::perform(fetch_balance(miner), move |status| {
-                Message::BalanceFetched(i, status)
+        let generation = self.generation;
+        for a in &self.accounts {
+            let (id, endpoint, miner) = (a.id, Arc::clone(&a.endpoint), Arc::clone(&a.miner));
+            commands.push(Task::perform(
+                fetch_balance(endpoint, miner),
+                move |result| Message::BalanceFetched(generation, id, result),
+            ));
+        }
+        Task::batch(commands)
+    }
+
+    /// Groups accounts by RPC endpoint, so each endpoint serves its miners in a few bulk reads.
+    /// Accounts read with different commitments are kept in separate groups.
+    pub fn refresh_accounts_in_batches(&self) -> Task<Message> {
+        let mut groups: HashMap<(&str, Commitment), (Arc<Endpoint>, Vec<(u64, Pubkey)>)> =
+            HashMap::new();
+        let mut invalid = vec![];
+        for a in &self.accounts {
+            let signer = match a.miner.try_signer() {
+                Ok(signer) => signer,
+                Err(e) => {
+                    invalid.push((a.id, Err(FetchError::InvalidKey(e))));
+                    continue;
+                }
+            };
+            let (_, miners) = groups
+                .entry((&a.json_rpc_url, a.miner.read_commitment))
+                .or_insert_with(|| (Arc::clone(&a.endpoint), vec![]));
+            miners.push((a.id, signer.pubkey()));
+        }
+        let generation = self.generation;
+        let mut commands: Vec<Task<Message>> = groups
+            .into_iter()
+            .map(|((_, commitment), (endpoint, miners))| {
+                Task::perform(fetch_batch(endpoint, commitment, miners), move |results| {
+                    Message::BatchFetched(generation, results)
+                })
+            })
+            .collect();
+        if !invalid.is_empty() {
+            commands.push(Task::perform(async { invalid }, move |results| {
+                Message::BatchFetched(generation, results)
             }));
         }
         Task::batch(commands)
     }
 
-    pub fn get_usd(&self, amount: f64) -> f64 {
-        round_dp(amount * self.price_usd, USD_PRECISION)
+    /// The value of an amount of ORE in the display currency.
+    pub fn get_value(&self, amount: f64) -> f64 {
+        amount * self.price
+    }
+
+    /// Formats the value of an amount with the currency symbol, or a placeholder
+    /// while the price is unknown.
+    pub fn format_value(&self, amount: f64) -> String {
+        if 0.0 == self.price {
+            "--".to_string()
+        } else {
+            self.currency.format(self.get_value(amount))
+        }
     }
 
     pub fn calculate_price(&self) -> Task<Message> {
-        let client = Arc::clone(&self.price_client);
-        Task::perform(fetch_price(client), Message::PriceFetched)
+        let feed = Arc::clone(&self.price_feed);
+        // On-chain prices are read from mainnet-beta pools, through the client of an account
+        // on a mainnet-beta endpoint, preferring a healthy one
+        let mainnet: Vec<&Account> = self
+            .accounts
+            .iter()
+            .filter(|a| self.cluster_of(a) == Cluster::MainnetBeta)
+            .collect();
+        let rpc_client = mainnet
+            .iter()
+            .find(|a| self.is_endpoint_healthy(&a.json_rpc_url))
+            .or(mainnet.first())
+            .map(|a| Arc::clone(&a.miner.rpc_client));
+        let context = PriceContext {
+            rpc_client,
+            currency: self.currency.clone(),
+        };
+        Task::perform(fetch_price(feed, context), Message::PriceFetched)
     }
 }
 
-pub fn create_account(json_rpc_url: String, keypair_path: String, priority_fee: u64) -> Account {
-    let rpc_client =
-        RpcClient::new_with_commitment(json_rpc_url.clone(), CommitmentConfig::confirmed());
+pub fn create_account(id: u64, endpoint: Arc<Endpoint>, config: &Config) -> Account {
+    // Accounts on the same endpoint share its RPC client
     let miner = Arc::new(Miner::new(
-        Arc::new(rpc_client),
-        priority_fee,
-        Some(keypair_path.clone()),
+        Arc::clone(&endpoint.rpc_client),
+        config.priority_fee,
+        Some(config.keypair_path.clone()),
+        config.read_commitment,
+        config.confirm_commitment,
     ));
+    let mut rpc_urls = vec![endpoint.url.clone()];
+    rpc_urls.extend(config.fallback_rpc_urls.iter().cloned());
     Account {
-        json_rpc_url,
+        id,
+        json_rpc_url: endpoint.url.clone(),
+        rpc_urls,
+        rpc_index: 0,
+        endpoint,
         miner,
         status: MinerStatus::default(),
         prepared: false,
+        error: None,
+        retries: 0,
+        retrying: false,
+        failures: 0,
+        earnings: None,
+    }
+}
+
+impl Account {
+    /// Applies a fetch result. A failed fetch keeps the last good status, which is then stale.
+    /// Whether the miner is online is judged by the given active period in seconds.
+    pub fn apply_result(&mut self, result: FetchResult, active_period: i64) {
+        match result {
+            Ok(status) => {
+                self.status = status;
+                self.status.is_online = is_active(self.status.last_hash_timestamp, active_period);
+                self.error = None;
+                self.retries = 0;
+            }
+            Err(FetchError::MissingProof(authority)) => {
+                self.status = MinerStatus {
+                    authority,
+                    ..Default::default()
+                };
+                self.error = Some(FetchError::MissingProof(authority));
+                self.retries = 0;
+            }
+            Err(e) => {
+                if e.is_retryable() {
+                    self.retries += 1;
+                }
+                self.error = Some(e);
+            }
+        }
+        self.prepared = true;
+    }
+
+    /// Finds the next endpoint of the account, in the configured order and wrapping
+    /// around, that passes the health check.
+    pub fn next_endpoint(&self, is_healthy: impl Fn(&str) -> bool) -> Option<usize> {
+        let count = self.rpc_urls.len();
+        (1..count)
+            .map(|step| (self.rpc_index + step) % count)
+            .find(|index| is_healthy(&self.rpc_urls[*index]))
+    }
+
+    /// Points the account at another endpoint, rebuilding its miner on the endpoint's client.
+    pub fn set_endpoint(&mut self, endpoint: Arc<Endpoint>) {
+        self.miner = Arc::new(Miner::new(
+            Arc::clone(&endpoint.rpc_client),
+            self.miner.priority_fee,
+            self.miner.keypair_filepath.clone(),
+            self.miner.read_commitment,
+            self.miner.confirm_commitment,
+        ));
+        self.json_rpc_url = endpoint.url.clone();
+        self.endpoint = endpoint;
+        self.failures = 0;
+    }
+
+    /// Rebuilds the miner with other commitments for reads and confirmations.
+    pub fn set_commitment(&mut self, kind: CommitmentKind, commitment: Commitment) {
+        let (mut read, mut confirm) = (self.miner.read_commitment, self.miner.confirm_commitment);
+        match kind {
+            CommitmentKind::Read => read = commitment,
+            CommitmentKind::Confirm => confirm = commitment,
+        }
+        self.miner = Arc::new(Miner::new(
+            Arc::clone(&self.endpoint.rpc_client),
+            self.miner.priority_fee,
+            self.miner.keypair_filepath.clone(),
+            read,
+            confirm,
+        ));
+    }
+
+    /// Checks whether the shown status is outdated because the latest fetch failed.
+    pub fn is_stale(&self) -> bool {
+        self.error.is_some() && self.status.is_valid
     }
 }
 
@@ -477,6 +1640,14 @@ pub fn get_accounts_summary(accounts: &Vec<Account>) -> (f64, f64, usize) {
     format_accounts_data(total_balance, total_stake, active_nodes)
 }
 
+/// Adds up the earnings of the miners with a known rate.
+pub fn get_fleet_earnings(accounts: &[Account]) -> Option<Earnings> {
+    accounts
+        .iter()
+        .filter_map(|a| a.earnings)
+        .reduce(|a, b| a + b)
+}
+
 pub fn format_accounts_data(
     total_balance: f64,
     total_stake: f64,
@@ -494,12 +1665,43 @@ pub fn save_user_config(dashboard: &mut Dashboard) {
     {
         println!("{:?},{:?}", dashboard.configs, dashboard.theme);
     }
+    let current = Profile {
+        name: dashboard.profile.clone(),
+        configs: dashboard.configs.clone(),
+        settings: Settings {
+            auto_refresh: dashboard.auto_refresh,
+            fetch_mode: dashboard.fetch_mode,
+            data_interval: dashboard.data_interval,
+            save_interval: dashboard.save_interval,
+            ..dashboard.settings.clone()
+        },
+    };
+    // A config that couldn't be loaded is kept, instead of being replaced by the empty one
+    if dashboard.config_error.is_some() {
+        if let Err(e) = config::set_aside(&dashboard.config_path) {
+            eprintln!(
+                "Failed to set aside the unreadable config, not saving: {}",
+                e
+            );
+            return;
+        }
+        dashboard.config_error = None;
+    }
+    let profiles = replace_profile(&dashboard.profiles, &current);
     match save_config(
         &Configs {
-            configs: dashboard.configs.clone(),
+            version: CONFIG_VERSION,
+            profile: dashboard.profile.clone(),
+            profiles,
             theme: dashboard.theme.to_string(),
+            history: dashboard.retention.clone(),
+            alerts: dashboard.alerts_config.clone(),
+            automation_paused: dashboard.automation_paused,
+            schedules: dashboard.jobs.iter().map(|j| j.config.clone()).collect(),
+            price: dashboard.price_config.clone(),
+            currency: dashboard.currency.clone(),
         },
-        USER_CONFIG_FILE,
+        &dashboard.config_path,
     ) {
         Ok(_) => println!("Config saved successfully"),
         Err(e) => eprintln!("Failed to save config: {}", e),
@@ -507,37 +1709,319 @@ pub fn save_user_config(dashboard: &mut Dashboard) {
     dashboard.is_saved = true;
 }
 
-pub async fn fetch_accounts_balance(miners: Vec<Arc<Miner>>) -> Vec<MinerStatus> {
+/// The time a refresh may take before pending accounts are reported as timed out.
+/// Accounts queue up behind the limits of their endpoint, which is taken into account.
+pub fn refresh_deadline(fetch_mode: FetchMode, accounts: &[Account]) -> Duration {
+    let mut queued: HashMap<&str, (&Endpoint, u32)> = HashMap::new();
+    for a in accounts {
+        queued.entry(&a.json_rpc_url).or_insert((&a.endpoint, 0)).1 += 1;
+    }
+    let (mut rounds, mut budget) = (0, Duration::ZERO);
+    for (endpoint, count) in queued.values() {
+        let (endpoint_rounds, requests) = match fetch_mode {
+            // Chunks of an endpoint are read one after another
+            FetchMode::Batch => {
+                let chunks = (count * 4 + 1).div_ceil(MAX_MULTIPLE_ACCOUNTS as u32);
+                (chunks, chunks)
+            }
+            _ => (
+                count.div_ceil(RPC_MAX_CONCURRENT_REQUESTS as u32),
+                count * BALANCE_REQUESTS,
+            ),
+        };
+        rounds = rounds.max(endpoint_rounds);
+        budget = budget.max(endpoint.budget_time(requests));
+    }
+    // Serial mode fetches one account after another
+    if let FetchMode::Serial = fetch_mode {
+        rounds = accounts.len() as u32;
+    }
+    Duration::from_secs(FETCH_TIMEOUT_SECONDS * rounds.max(1) as u64 + REFRESH_GRACE_SECONDS)
+        + budget
+}
+
+/// The settings with the values of the settings form, or why the form is invalid.
+fn parse_settings(form: &SettingsForm, settings: &Settings) -> Result<Settings, String> {
+    let seconds = |value: &str, name: &str| match value.trim().parse::<u64>() {
+        Ok(seconds) if seconds > 0 => Ok(seconds),
+        _ => Err(format!(
+            "{} must be a whole number of seconds above 0",
+            name
+        )),
+    };
+    let data_interval = seconds(&form.data_interval, "Refresh interval")?;
+    let save_interval = seconds(&form.save_interval, "Save interval")?;
+    let active_period_seconds = seconds(&form.active_period, "Online threshold")? as i64;
+    let json_rpc_url = form.json_rpc_url.trim().to_string();
+    if Url::parse(&json_rpc_url).is_err() {
+        return Err(format!("{} is not a valid url", json_rpc_url));
+    }
+    let priority_fee = form
+        .priority_fee
+        .trim()
+        .parse::<u64>()
+        .map_err(|_| "Priority fee must be a whole number".to_string())?;
+    Ok(Settings {
+        data_interval,
+        save_interval,
+        active_period_seconds,
+        json_rpc_url,
+        priority_fee,
+        ..settings.clone()
+    })
+}
+
+/// The delay before retrying a fetch, doubling with every consecutive failure.
+pub fn retry_delay(retries: u32) -> u64 {
+    RETRY_BASE_SECONDS * 2u64.pow(retries.saturating_sub(1))
+}
+
+pub async fn fetch_accounts_balance(
+    miners: Vec<(u64, Arc<Endpoint>, Arc<Miner>)>,
+) -> Vec<(u64, FetchResult)> {
     let mut accounts_status = vec![];
-    for miner in miners {
+    for (id, endpoint, miner) in miners {
         // Retrieve the status of an account
-        let status = miner.balance(None).await;
+        let result = fetch_balance(endpoint, miner).await;
         // Gather the status information for an account
-        accounts_status.push(status);
+        accounts_status.push((id, result));
     }
     accounts_status
 }
 
-pub async fn fetch_balance(miner: Arc<Miner>) -> MinerStatus {
-    miner.balance(None).await
+pub async fn fetch_balance(endpoint: Arc<Endpoint>, miner: Arc<Miner>) -> FetchResult {
+    // Wait for the endpoint's limits before the timeout starts
+    let _permit = endpoint.acquire().await;
+    match tokio::time::timeout(
+        Duration::from_secs(FETCH_TIMEOUT_SECONDS),
+        miner.balance(None),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(_) => Err(FetchError::Timeout),
+    }
 }
 
-pub async fn fetch_price(client: Arc<CoinGecko>) -> f64 {
-    if let Ok(rep) = client.get().await {
-        #[cfg(debug_assertions)]
-        {
-            println!("Price response: {:?}", rep);
-        }
-        rep[ORE_TOKEN_ID].usd.unwrap_or_default()
-    } else {
-        0.0
-    }
+pub async fn retry_balance(endpoint: Arc<Endpoint>, miner: Arc<Miner>, delay: u64) -> FetchResult {
+    tokio::time::sleep(Duration::from_secs(delay)).await;
+    fetch_balance(endpoint, miner).await
+}
+
+pub async fn fetch_price(
+    feed: Arc<PriceFeed>,
+    context: PriceContext,
+) -> Result<PriceQuote, String> {
+    feed.quote(&context).await
 }
 
-pub async fn request_claim(miner: Arc<Miner>, params: ClaimParams) -> bool {
+pub async fn request_claim(miner: Arc<Miner>, params: ClaimParams) -> Result<Signature, String> {
     miner.claim(params).await
 }
 
-pub async fn request_stake(miner: Arc<Miner>, params: StakeParams) -> bool {
+pub async fn request_stake(miner: Arc<Miner>, params: StakeParams) -> Result<Signature, String> {
     miner.stake(params).await
 }
+
+pub async fn request_transfer(
+    miner: Arc<Miner>,
+    params: TransferParams,
+) -> Result<Signature, String> {
+    miner.transfer(params).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consts::{ACTIVE_PERIOD_SECONDS, RPC_REQUESTS_PER_SECOND};
+
+    fn endpoint(url: &str) -> Arc<Endpoint> {
+        Arc::new(Endpoint::new(
+            url.to_string(),
+            RPC_MAX_CONCURRENT_REQUESTS,
+            RPC_REQUESTS_PER_SECOND,
+        ))
+    }
+
+    fn account_on(id: u64, endpoint: &Arc<Endpoint>) -> Account {
+        create_account(id, Arc::clone(endpoint), &Config::default())
+    }
+
+    fn account(id: u64) -> Account {
+        account_on(id, &endpoint("http://localhost:8899"))
+    }
+
+    fn valid_status() -> MinerStatus {
+        MinerStatus {
+            is_valid: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn serial_refresh_waits_for_every_account_in_turn() {
+        let shared = endpoint("http://localhost:8899");
+        let accounts: Vec<Account> = (1..=3).map(|id| account_on(id, &shared)).collect();
+        let budget = shared.budget_time(3 * BALANCE_REQUESTS);
+        assert_eq!(
+            refresh_deadline(FetchMode::Serial, &accounts),
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS * 3 + REFRESH_GRACE_SECONDS) + budget
+        );
+        assert_eq!(
+            refresh_deadline(FetchMode::Parallel, &accounts),
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS + REFRESH_GRACE_SECONDS) + budget
+        );
+        assert_eq!(
+            refresh_deadline(FetchMode::Serial, &[]),
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS + REFRESH_GRACE_SECONDS)
+        );
+    }
+
+    #[test]
+    fn batch_refresh_reads_chunks_of_each_endpoint_in_turn() {
+        // The config, then a proof, token account, wallet and legacy token account per miner,
+        // fill five chunks
+        let shared = endpoint("http://localhost:8899");
+        let accounts: Vec<Account> = (1..=MAX_MULTIPLE_ACCOUNTS as u64)
+            .map(|id| account_on(id, &shared))
+            .collect();
+        assert_eq!(
+            refresh_deadline(FetchMode::Batch, &accounts),
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS * 5 + REFRESH_GRACE_SECONDS)
+                + shared.budget_time(5)
+        );
+
+        // Endpoints are read side by side, so the busiest one sets the deadline
+        let other = endpoint("http://localhost:8900");
+        let mut accounts = accounts;
+        accounts.push(account_on(0, &other));
+        assert_eq!(
+            refresh_deadline(FetchMode::Batch, &accounts),
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS * 5 + REFRESH_GRACE_SECONDS)
+                + shared.budget_time(5)
+        );
+    }
+
+    #[test]
+    fn parallel_refresh_queues_behind_the_endpoint_concurrency() {
+        let shared = endpoint("http://localhost:8899");
+        let count = RPC_MAX_CONCURRENT_REQUESTS as u64 + 1;
+        let accounts: Vec<Account> = (1..=count).map(|id| account_on(id, &shared)).collect();
+        assert_eq!(
+            refresh_deadline(FetchMode::Parallel, &accounts),
+            Duration::from_secs(FETCH_TIMEOUT_SECONDS * 2 + REFRESH_GRACE_SECONDS)
+                + shared.budget_time(count as u32 * BALANCE_REQUESTS)
+        );
+    }
+
+    #[test]
+    fn failover_tries_the_fallbacks_in_order() {
+        let primary = endpoint("http://primary");
+        let config = Config {
+            fallback_rpc_urls: vec!["http://first".to_string(), "http://second".to_string()],
+            ..Default::default()
+        };
+        let mut account = create_account(1, primary, &config);
+        assert_eq!(account.next_endpoint(|_| true), Some(1));
+        assert_eq!(account.next_endpoint(|url| url != "http://first"), Some(2));
+        assert_eq!(account.next_endpoint(|_| false), None);
+
+        // The last fallback wraps around to the primary endpoint
+        account.rpc_index = 2;
+        assert_eq!(account.next_endpoint(|_| true), Some(0));
+    }
+
+    #[test]
+    fn account_without_fallbacks_stays_on_its_endpoint() {
+        assert_eq!(account(1).next_endpoint(|_| true), None);
+    }
+
+    #[test]
+    fn retries_back_off_exponentially() {
+        assert_eq!(retry_delay(1), RETRY_BASE_SECONDS);
+        assert_eq!(retry_delay(2), RETRY_BASE_SECONDS * 2);
+        assert_eq!(retry_delay(4), RETRY_BASE_SECONDS * 8);
+    }
+
+    #[test]
+    fn failed_fetch_keeps_the_last_status_as_stale() {
+        let mut account = account(1);
+        account.apply_result(Ok(valid_status()), ACTIVE_PERIOD_SECONDS);
+        account.apply_result(Err(FetchError::Timeout), ACTIVE_PERIOD_SECONDS);
+        account.apply_result(
+            Err(FetchError::Rpc("429 Too Many Requests".to_string())),
+            ACTIVE_PERIOD_SECONDS,
+        );
+        assert!(account.status.is_valid);
+        assert!(account.is_stale());
+        assert_eq!(account.retries, 2);
+
+        account.apply_result(Ok(valid_status()), ACTIVE_PERIOD_SECONDS);
+        assert!(!account.is_stale());
+        assert_eq!(account.retries, 0);
+    }
+
+    #[test]
+    fn missing_proof_is_not_retried() {
+        let mut account = account(1);
+        let authority = Pubkey::new_unique();
+        account.apply_result(Ok(valid_status()), ACTIVE_PERIOD_SECONDS);
+        account.apply_result(
+            Err(FetchError::MissingProof(authority)),
+            ACTIVE_PERIOD_SECONDS,
+        );
+        assert!(!account.status.is_valid);
+        assert_eq!(account.status.authority, authority);
+        assert_eq!(account.retries, 0);
+        assert!(!FetchError::MissingProof(authority).is_retryable());
+    }
+
+    fn settings_form() -> SettingsForm {
+        SettingsForm {
+            data_interval: " 30 ".to_string(),
+            save_interval: "10".to_string(),
+            active_period: "120".to_string(),
+            json_rpc_url: "https://api.mainnet-beta.solana.com".to_string(),
+            priority_fee: "5000".to_string(),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn settings_form_replaces_the_edited_settings() {
+        let current = Settings {
+            auto_refresh: false,
+            ..Default::default()
+        };
+        let settings = parse_settings(&settings_form(), &current).unwrap();
+        assert_eq!(settings.data_interval, 30);
+        assert_eq!(settings.save_interval, 10);
+        assert_eq!(settings.active_period_seconds, 120);
+        assert_eq!(settings.json_rpc_url, "https://api.mainnet-beta.solana.com");
+        assert_eq!(settings.priority_fee, 5000);
+        assert!(!settings.auto_refresh);
+    }
+
+    #[test]
+    fn settings_form_rejects_invalid_values() {
+        let current = Settings::default();
+        let zero = SettingsForm {
+            data_interval: "0".to_string(),
+            ..settings_form()
+        };
+        assert_eq!(
+            parse_settings(&zero, &current).unwrap_err(),
+            "Refresh interval must be a whole number of seconds above 0"
+        );
+        let url = SettingsForm {
+            json_rpc_url: "localhost".to_string(),
+            ..settings_form()
+        };
+        assert!(parse_settings(&url, &current).is_err());
+        let fee = SettingsForm {
+            priority_fee: "-1".to_string(),
+            ..settings_form()
+        };
+        assert!(parse_settings(&fee, &current).is_err());
+    }
+}
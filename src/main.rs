@@ -1,39 +1,85 @@
+mod alerts;
+mod automation;
 mod balance;
+mod batch;
+mod chart;
 mod circular;
 mod claim;
+mod cluster;
+mod config;
 mod consts;
 mod cu_limits;
+mod earnings;
 mod easing;
+mod health;
+mod history;
 mod logic;
 mod miner;
+mod pool_price;
+mod portfolio;
 mod price;
+mod rpc_pool;
+mod scheduler;
 mod send_and_confirm;
 mod stake;
 mod style;
+mod subscribe;
+mod transfer;
+mod uptime;
 mod utils;
 mod views;
 
 use crate::{
-    balance::MinerStatus,
-    consts::{SOLANA_DEFAULT_KEYPAIR, USER_CONFIG_FILE, WINDOW_SIZE},
+    alerts::{create_sinks, Alert, AlertEngine, AlertSink, AlertsConfig},
+    automation::AutomationRun,
+    balance::{FetchError, MinerStatus},
+    chart::ChartRange,
+    cluster::Commitment,
+    consts::{
+        AUTOMATION_LOG_SECONDS, DEFAULT_PROFILE, DEFAULT_RPC_URL, HEALTH_CHECK_INTERVAL_SECONDS,
+        HISTORY_DB_FILE, SCHEDULER_TICK_SECONDS, SCHEDULE_LOG_SIZE, SOLANA_DEFAULT_KEYPAIR,
+        USER_CONFIG_FILE, WINDOW_SIZE,
+    },
+    earnings::Earnings,
+    health::EndpointHealth,
+    history::{history_path, History, PricePoint, Snapshot},
     logic::{create_account, FetchMode, Message, ModalType},
-    miner::{Config, Miner},
+    miner::{select_profile, Config, Miner, Profile, Retention, Settings},
+    rpc_pool::{Endpoint, RpcPool},
+    scheduler::{Job, ScheduleRun},
+    subscribe::watch_endpoint,
+    uptime::Uptime,
     utils::{get_theme, load_config},
     views::add_account_view,
 };
 use iced::event::{self};
 use iced::{Element, Subscription, Theme};
-use price::CoinGecko;
+use price::{Currency, PriceConfig, PriceFeed, PriceQuote};
+use solana_program::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
 
 fn main() -> iced::Result {
+    // Copy the files earlier versions kept in the working directory to the platform directories
+    config::migrate_location(USER_CONFIG_FILE, &config::config_path());
+    config::migrate_location(HISTORY_DB_FILE, &history_path());
+    // Profiles are added on the dashboard, a name on the command line has to exist
+    let profile = config::cli_profile(std::env::args().skip(1));
+    if let Some(name) = &profile {
+        if let Ok(configs) = load_config(&config::config_path()) {
+            if let Err(e) = config::check_profile(name, &configs.profiles) {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+    }
     iced::application(Dashboard::title, Dashboard::update, Dashboard::view)
         .theme(Dashboard::theme)
         .window_size(WINDOW_SIZE)
         .exit_on_close_request(false)
         .subscription(Dashboard::subscription)
-        .run_with(Dashboard::load)
+        .run_with(move || Dashboard::load(profile))
 }
 struct Dashboard {
     modal_view: fn(&Dashboard) -> Element<Message>,
@@ -43,39 +89,90 @@ struct Dashboard {
     is_claim_process: bool,
     is_stake_process: bool,
     fetch_mode: FetchMode,
-    fetch_count: usize,
-    data_interval: u64, // Interval for fetching data in seconds
-    save_interval: u64, // Interval for saving config in seconds
+    generation: u64,               // Generation of the latest refresh
+    pending: HashSet<u64>,         // Accounts the latest refresh is waiting for
+    unsubscribed: HashSet<Pubkey>, // Miners whose websocket subscription is unavailable
+    data_interval: u64,            // Interval for fetching data in seconds
+    save_interval: u64,            // Interval for saving config in seconds
+    settings: Settings,            // Saved settings, including the defaults of new accounts
+    settings_form: SettingsForm,
     is_saved: bool,
-    configs: Vec<Config>, // User's config settings
+    profile: String,        // Name of the profile in use
+    profiles: Vec<Profile>, // Every profile, as last loaded or saved
+    new_profile: String,
+    new_profile_rpc_url: String,
+    configs: Vec<Config>,         // User's config settings
+    config_path: PathBuf,         // Where the user config is read from and saved to
+    config_error: Option<String>, // Why the user config couldn't be loaded
     json_rpc_url: String,
+    fallback_rpc_urls: String, // Comma separated fallback endpoints of a new account
     keypair: String,
     priority_fee: String,
-    current_index: Option<usize>, // Current index of selected account
+    read_commitment: Commitment,
+    confirm_commitment: Commitment,
+    current_id: Option<u64>, // Id of the selected account
+    next_id: u64,            // Last id handed out to an account
     accounts: Vec<Account>,
+    rpc_pool: RpcPool,        // RPC endpoints shared by accounts
+    history: Option<History>, // Local database of status snapshots
+    retention: Retention,
+    last_compacted: i64, // Unix timestamp of the last history compaction
+    chart_range: ChartRange,
+    snapshots: Vec<Snapshot>, // History of the selected account within the chart range
+    uptime: Vec<Uptime>,      // Uptime of the selected account for each reported range
+    timeline: Uptime,         // Uptime of the selected account within the chart range
+    health: HashMap<String, EndpointHealth>, // Health of the endpoints by URL
+    move_target: Option<String>, // Endpoint to move accounts off unhealthy endpoints to
+    alerts_config: AlertsConfig,
+    alert_engine: AlertEngine,
+    alert_sinks: Vec<Arc<dyn AlertSink>>, // Where fired alerts are delivered
+    alerts: VecDeque<Alert>,              // Fired alerts, newest first
+    automation_paused: bool,
+    automation_runs: Vec<AutomationRun>, // Recent automation runs, oldest first
+    automating: HashSet<u64>,            // Accounts with an automation run in flight
+    jobs: Vec<Job>,                      // Scheduled jobs
+    schedule_runs: VecDeque<ScheduleRun>, // Runs of scheduled jobs, newest first
     stake: f64,
-    stake_usd: String,
+    stake_value: String, // Stake in the display currency
     balance: f64,
-    balance_usd: String,
+    balance_value: String, // Balance in the display currency
     active_num: usize,
-    extend_items_per_row: u8, // Extended items per row in UI
+    earnings: Option<Earnings>, // Earnings of the fleet
+    extend_items_per_row: u8,   // Extended items per row in UI
     theme: Theme,
     claim_address: String,
     claim_amount: String,
     stake_amount: String,
+    projection_amount: String,
     version: String,
     dialog: Dialog,
-    price_client: Arc<CoinGecko>, // Client for fetching price data
-    price_usd: f64,
+    price_config: PriceConfig,
+    price_feed: Arc<PriceFeed>, // Sources for fetching price data
+    currency: Currency,
+    price: f64,                     // ORE price in the display currency
+    sol_price: f64,                 // SOL price in the display currency, 0 while unknown
+    price_history: Vec<PricePoint>, // Stored prices in the display currency, oldest first
+    price_range: ChartRange,
+    price_quote: Option<PriceQuote>, // Latest successful price quote
+    price_error: Option<String>,     // Why the latest price fetch failed
 }
 
 /// Represents a user account with associated data.
 #[derive(Clone)]
 struct Account {
-    json_rpc_url: String,
+    id: u64,               // Stable id, unlike the position in the list
+    json_rpc_url: String,  // Endpoint in use
+    rpc_urls: Vec<String>, // Primary endpoint followed by the fallbacks
+    rpc_index: usize,      // Position of the endpoint in use in rpc_urls
+    endpoint: Arc<Endpoint>,
     miner: Arc<Miner>,
     status: MinerStatus,
     prepared: bool,
+    error: Option<FetchError>, // Error of the latest fetch
+    retries: u32,              // Consecutive transient fetch failures
+    retrying: bool,
+    failures: u32, // Consecutive failures on the endpoint in use
+    earnings: Option<Earnings>,
 }
 
 /// Enum for different content types in dialogs.
@@ -92,6 +189,17 @@ impl Default for ContentType {
     }
 }
 
+/// The settings being edited, applied once they all validate.
+#[derive(Default)]
+struct SettingsForm {
+    data_interval: String,
+    save_interval: String,
+    active_period: String,
+    json_rpc_url: String,
+    priority_fee: String,
+    error: Option<String>,
+}
+
 /// Represents the state of a dialog in the UI.
 #[derive(Default)]
 struct Dialog {
@@ -100,7 +208,9 @@ struct Dialog {
 }
 
 impl Dashboard {
-    fn init() -> Self {
+    /// Restores the dashboard from the user config, in the given profile or else the
+    /// one used last.
+    fn init(profile: Option<String>) -> Self {
         // Set the default keypair path based on the user's home directory
         let mut default_keypair_path = PathBuf::new();
         if let Some(home_path) = dirs::home_dir() {
@@ -110,59 +220,166 @@ impl Dashboard {
 
         // Restore account list from user configurations
         let mut accounts: Vec<Account> = vec![];
-        let mut user_configs = vec![];
+        let mut rpc_pool = RpcPool::default();
+        let mut profiles = vec![];
+        let mut profile_name = DEFAULT_PROFILE.to_string();
         let mut user_theme = Theme::Light;
-        match load_config(USER_CONFIG_FILE) {
+        let mut retention = Retention::default();
+        let mut alerts_config = AlertsConfig::default();
+        let mut automation_paused = false;
+        let mut schedules = vec![];
+        let mut price_config = PriceConfig::default();
+        let mut currency = Currency::default();
+        // Read the config from the platform config directory
+        let config_path = config::config_path();
+        let mut config_error = None;
+        match load_config(&config_path) {
             Ok(configs) => {
-                // Load user's keypair
-                for config in &configs.configs {
-                    let account = create_account(
-                        config.json_rpc_url.clone(),
-                        config.keypair_path.clone(),
-                        config.priority_fee,
-                    );
-                    accounts.push(account);
+                profiles = configs.profiles;
+                if !configs.profile.is_empty() {
+                    profile_name = configs.profile;
                 }
-                user_configs = configs.configs;
                 // Load user's preferred theme
                 user_theme = get_theme(&configs.theme);
+                retention = configs.history;
+                alerts_config = configs.alerts;
+                automation_paused = configs.automation_paused;
+                schedules = configs.schedules;
+                price_config = configs.price;
+                currency = configs.currency;
+            }
+            Err(e) => {
+                eprintln!("Failed to load user's config: {}", e);
+                config_error = Some(e.to_string());
             }
-            Err(e) => eprintln!("Failed to load user's config: {}", e),
         }
-        println!("Loaded accounts {:?}", accounts.len());
+        // A profile asked for wins, and is created when the config lacks it, as a new config
+        // lacks the default profile
+        if let Some(name) = profile {
+            profile_name = name;
+        }
+        let Profile {
+            configs: user_configs,
+            settings,
+            ..
+        } = select_profile(&mut profiles, &profile_name);
+        // Load user's keypair
+        for (id, config) in (1..).zip(&user_configs) {
+            let account = create_account(id, rpc_pool.get(&config.json_rpc_url), config);
+            accounts.push(account);
+        }
+        println!(
+            "Loaded accounts {:?} of profile {}",
+            accounts.len(),
+            profile_name
+        );
+
+        // Open the history of status snapshots, which is optional for the dashboard
+        let history = match History::open(&history_path()) {
+            Ok(history) => Some(history),
+            Err(e) => {
+                eprintln!("Failed to open history: {}", e);
+                None
+            }
+        };
+        // Restore the recent automation runs, which intervals and daily caps count on
+        let now = chrono::Utc::now().timestamp();
+        let since = now - AUTOMATION_LOG_SECONDS;
+        let automation_runs = match history.as_ref().map(|h| h.runs(since)) {
+            Some(Ok(runs)) => runs,
+            Some(Err(e)) => {
+                eprintln!("Failed to load automation runs: {}", e);
+                vec![]
+            }
+            None => vec![],
+        };
+        let schedule_runs = match history.as_ref().map(|h| h.schedule_runs(SCHEDULE_LOG_SIZE)) {
+            Some(Ok(runs)) => runs.into(),
+            Some(Err(e)) => {
+                eprintln!("Failed to load scheduled runs: {}", e);
+                VecDeque::new()
+            }
+            None => VecDeque::new(),
+        };
+        let jobs = schedules
+            .into_iter()
+            .map(|config| Job::new(config, now))
+            .collect();
 
         Self {
             modal_view: add_account_view,
             show_modal: ModalType::Main,
-            auto_refresh: true,
+            auto_refresh: settings.auto_refresh,
             is_refreshed: false,
             is_claim_process: false,
             is_stake_process: false,
-            fetch_mode: FetchMode::Parallel,
-            fetch_count: 0,
-            data_interval: 60,
-            save_interval: 5,
+            fetch_mode: settings.fetch_mode,
+            generation: 0,
+            pending: HashSet::new(),
+            unsubscribed: HashSet::new(),
+            data_interval: settings.data_interval,
+            save_interval: settings.save_interval,
             is_saved: true,
+            profile: profile_name,
+            profiles,
+            new_profile: String::default(),
+            new_profile_rpc_url: DEFAULT_RPC_URL.to_string(),
+            config_path,
+            config_error,
             configs: user_configs,
-            json_rpc_url: "https://api.devnet.solana.com".to_string(),
+            json_rpc_url: settings.json_rpc_url.clone(),
+            fallback_rpc_urls: String::default(),
             keypair: default_keypair_path.display().to_string(),
-            priority_fee: "10".to_string(),
-            current_index: None,
+            priority_fee: settings.priority_fee.to_string(),
+            read_commitment: Commitment::default(),
+            confirm_commitment: Commitment::default(),
+            settings,
+            settings_form: SettingsForm::default(),
+            current_id: None,
+            next_id: accounts.len() as u64,
             accounts,
+            rpc_pool,
+            health: HashMap::new(),
+            move_target: None,
+            alert_sinks: create_sinks(&alerts_config.sinks),
+            alerts_config,
+            alert_engine: AlertEngine::default(),
+            alerts: VecDeque::new(),
+            automation_paused,
+            automation_runs,
+            automating: HashSet::new(),
+            jobs,
+            schedule_runs,
+            history,
+            retention,
+            last_compacted: 0,
+            chart_range: ChartRange::Day,
+            snapshots: vec![],
+            uptime: vec![],
+            timeline: Uptime::default(),
             active_num: 0,
+            earnings: None,
             stake: 0.0,
-            stake_usd: String::default(),
+            stake_value: String::default(),
             balance: 0.0,
-            balance_usd: String::default(),
+            balance_value: String::default(),
             extend_items_per_row: 0,
             theme: user_theme,
             claim_address: String::default(),
             claim_amount: String::default(),
             stake_amount: String::default(),
+            projection_amount: String::default(),
             version: env!("CARGO_PKG_VERSION").to_string(),
             dialog: Dialog::default(),
-            price_client: Arc::new(CoinGecko::default()),
-            price_usd: 0.0,
+            price_feed: Arc::new(PriceFeed::new(&price_config)),
+            price_config,
+            currency,
+            price: 0.0,
+            sol_price: 0.0,
+            price_history: vec![],
+            price_range: ChartRange::Day,
+            price_quote: None,
+            price_error: None,
         }
     }
 
@@ -171,9 +388,53 @@ impl Dashboard {
 
         // Add an event for refreshing data periodically
         if self.auto_refresh {
+            // Subscribed miners only need their online state and price refreshed,
+            // unless some websocket is unavailable and polling takes over
+            let every = iced::time::every(std::time::Duration::from_secs(self.data_interval));
+            events.push(match self.fetch_mode {
+                FetchMode::Subscribe if self.unsubscribed.is_empty() => {
+                    every.map(|_| Message::Heartbeat)
+                }
+                _ => every.map(|_| Message::Refresh),
+            });
+        }
+
+        // Watch the accounts of every known miner for changes
+        // over one websocket per endpoint
+        if let FetchMode::Subscribe = self.fetch_mode {
+            let mut endpoints: Vec<(Arc<Endpoint>, Vec<(Pubkey, Commitment)>)> = vec![];
+            for a in self.accounts.iter().filter(|a| a.status.is_valid) {
+                let authority = a.status.authority;
+                let miner = (authority, a.miner.read_commitment);
+                match endpoints.iter_mut().find(|(e, _)| e.url == a.json_rpc_url) {
+                    Some((_, miners)) if miners.iter().any(|(m, _)| *m == authority) => {}
+                    Some((_, miners)) => miners.push(miner),
+                    None => endpoints.push((Arc::clone(&a.endpoint), vec![miner])),
+                }
+            }
+            for (endpoint, miners) in endpoints {
+                events.push(Subscription::run_with_id(
+                    (endpoint.url.clone(), miners.clone()),
+                    watch_endpoint(endpoint, miners),
+                ));
+            }
+        }
+
+        // Probe the RPC endpoints periodically
+        if !self.accounts.is_empty() {
+            events.push(
+                iced::time::every(std::time::Duration::from_secs(
+                    HEALTH_CHECK_INTERVAL_SECONDS,
+                ))
+                .map(|_| Message::CheckHealth),
+            );
+        }
+
+        // Check for due jobs, unless none can run
+        if self.jobs.iter().any(|j| j.next_run.is_some()) {
             events.push(
-                iced::time::every(std::time::Duration::from_secs(self.data_interval))
-                    .map(|_| Message::Refresh),
+                iced::time::every(std::time::Duration::from_secs(SCHEDULER_TICK_SECONDS))
+                    .map(|_| Message::SchedulerTick),
             );
         }
 
@@ -193,7 +454,7 @@ impl Dashboard {
     }
 
     fn title(&self) -> String {
-        String::from("Ore dashboard")
+        format!("Ore dashboard - {}", self.profile)
     }
 
     fn theme(&self) -> iced::Theme {
@@ -1,25 +1,168 @@
+use crate::alerts::AlertsConfig;
+use crate::automation::AutomationRule;
+use crate::cluster::Commitment;
+use crate::consts::{
+    ACTIVE_PERIOD_SECONDS, DATA_INTERVAL_SECONDS, DEFAULT_PRIORITY_FEE, DEFAULT_RPC_URL,
+    HISTORY_DOWNSAMPLE_MINUTES, HISTORY_FULL_RESOLUTION_DAYS, HISTORY_RETENTION_DAYS,
+    SAVE_INTERVAL_SECONDS,
+};
+use crate::logic::FetchMode;
+use crate::price::{Currency, PriceConfig};
+use crate::scheduler::ScheduledJob;
 use serde::{Deserialize, Serialize};
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_sdk::signature::{read_keypair_file, Keypair};
 use std::sync::Arc;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Config {
     pub json_rpc_url: String,
+    /// Endpoints switched to in order when the primary one keeps failing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallback_rpc_urls: Vec<String>,
     pub keypair_path: String,
     pub priority_fee: u64,
+    /// How settled the state read for the miner must be
+    #[serde(default, skip_serializing_if = "Commitment::is_default")]
+    pub read_commitment: Commitment,
+    /// How settled a transaction must be before it counts as landed
+    #[serde(default, skip_serializing_if = "Commitment::is_default")]
+    pub confirm_commitment: Commitment,
+    /// Claims and stakes run automatically after every refresh
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub automation: Vec<AutomationRule>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Configs {
-    pub configs: Vec<Config>,
+    /// Version of the file format, see `config::upgrade`
+    #[serde(default)]
+    pub version: u32,
+    /// Name of the profile in use
+    #[serde(default)]
+    pub profile: String,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
     pub theme: String,
+    #[serde(default)]
+    pub history: Retention,
+    #[serde(default)]
+    pub alerts: AlertsConfig,
+    /// Stops every automation rule from running
+    #[serde(default)]
+    pub automation_paused: bool,
+    /// Claims, stakes and transfers run at fixed times
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduledJob>,
+    #[serde(default)]
+    pub price: PriceConfig,
+    /// The fiat currency or SOL values are shown in
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+/// A named set of accounts with its own settings, e.g. for mainnet or devnet miners.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub configs: Vec<Config>,
+    #[serde(default)]
+    pub settings: Settings,
+}
+
+impl Profile {
+    pub fn new(name: &str, settings: Settings) -> Self {
+        Profile {
+            name: name.to_string(),
+            configs: vec![],
+            settings,
+        }
+    }
+}
+
+/// The profile of a name, added with default settings when there is none yet.
+pub fn select_profile(profiles: &mut Vec<Profile>, name: &str) -> Profile {
+    match profiles.iter().find(|p| p.name == name) {
+        Some(profile) => profile.clone(),
+        None => {
+            let profile = Profile::new(name, Settings::default());
+            profiles.push(profile.clone());
+            profile
+        }
+    }
+}
+
+/// The profiles with the one in use as it is now, and the others as they were loaded.
+pub fn replace_profile(profiles: &[Profile], current: &Profile) -> Vec<Profile> {
+    profiles
+        .iter()
+        .map(|p| if p.name == current.name { current } else { p })
+        .cloned()
+        .collect()
+}
+
+/// Dashboard settings, restored on the next launch.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Settings {
+    pub auto_refresh: bool,
+    pub fetch_mode: FetchMode,
+    /// Seconds between refreshes.
+    pub data_interval: u64,
+    /// Seconds between saves of a changed config.
+    pub save_interval: u64,
+    /// Seconds after the last hash a miner still counts as online.
+    pub active_period_seconds: i64,
+    /// RPC endpoint suggested for new accounts.
+    pub json_rpc_url: String,
+    /// Priority fee suggested for new accounts.
+    pub priority_fee: u64,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            auto_refresh: true,
+            fetch_mode: FetchMode::Parallel,
+            data_interval: DATA_INTERVAL_SECONDS,
+            save_interval: SAVE_INTERVAL_SECONDS,
+            active_period_seconds: ACTIVE_PERIOD_SECONDS,
+            json_rpc_url: DEFAULT_RPC_URL.to_string(),
+            priority_fee: DEFAULT_PRIORITY_FEE,
+        }
+    }
+}
+
+/// How long status snapshots are kept, and at what resolution.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Retention {
+    /// Snapshots younger than this are kept as recorded.
+    pub full_resolution_days: u32,
+    /// Older snapshots are thinned out to one per bucket of this length.
+    pub downsample_minutes: u32,
+    /// Snapshots older than this are deleted.
+    pub retention_days: u32,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Retention {
+            full_resolution_days: HISTORY_FULL_RESOLUTION_DAYS,
+            downsample_minutes: HISTORY_DOWNSAMPLE_MINUTES,
+            retention_days: HISTORY_RETENTION_DAYS,
+        }
+    }
 }
 
 pub struct Miner {
     pub keypair_filepath: Option<String>,
     pub priority_fee: u64,
     pub rpc_client: Arc<RpcClient>,
+    /// Commitment of reads, which may differ from the shared client's.
+    pub read_commitment: Commitment,
+    pub confirm_commitment: Commitment,
 }
 
 impl Miner {
@@ -27,11 +170,24 @@ impl Miner {
         rpc_client: Arc<RpcClient>,
         priority_fee: u64,
         keypair_filepath: Option<String>,
+        read_commitment: Commitment,
+        confirm_commitment: Commitment,
     ) -> Self {
         Self {
             rpc_client,
             keypair_filepath,
             priority_fee,
+            read_commitment,
+            confirm_commitment,
+        }
+    }
+
+    /// Reads the keypair of the miner, reporting unreadable files instead of panicking.
+    pub fn try_signer(&self) -> Result<Keypair, String> {
+        match &self.keypair_filepath {
+            Some(filepath) => read_keypair_file(filepath)
+                .map_err(|e| format!("Failed to read keypair file {}: {}", filepath, e)),
+            None => Err("No keypair provided".to_string()),
         }
     }
 
@@ -42,3 +198,60 @@ impl Miner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_settings_take_their_defaults() {
+        let settings: Settings = toml::from_str("data_interval = 30").unwrap();
+        assert_eq!(settings.data_interval, 30);
+        assert_eq!(settings.save_interval, SAVE_INTERVAL_SECONDS);
+        assert_eq!(settings.active_period_seconds, ACTIVE_PERIOD_SECONDS);
+        assert!(settings.auto_refresh);
+    }
+
+    fn config(keypair_path: &str) -> Config {
+        Config {
+            json_rpc_url: DEFAULT_RPC_URL.to_string(),
+            keypair_path: keypair_path.to_string(),
+            priority_fee: DEFAULT_PRIORITY_FEE,
+            ..Default::default()
+        }
+    }
+
+    fn profile(name: &str, keypair_path: &str) -> Profile {
+        let mut profile = Profile::new(name, Settings::default());
+        profile.configs.push(config(keypair_path));
+        profile
+    }
+
+    #[test]
+    fn switching_profiles_keeps_their_accounts_apart() {
+        let mut profiles = vec![
+            profile("mainnet", "main.json"),
+            profile("devnet", "dev.json"),
+        ];
+        // Leaving mainnet with an account added saves it, leaving devnet as loaded
+        let mut mainnet = select_profile(&mut profiles, "mainnet");
+        mainnet.configs.push(config("second.json"));
+        let profiles = replace_profile(&profiles, &mainnet);
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].configs.len(), 2);
+
+        let mut profiles = profiles;
+        let devnet = select_profile(&mut profiles, "devnet");
+        assert_eq!(devnet.configs.len(), 1);
+        assert_eq!(devnet.configs[0].keypair_path, "dev.json");
+    }
+
+    #[test]
+    fn selecting_a_missing_profile_adds_it() {
+        let mut profiles = vec![];
+        let profile = select_profile(&mut profiles, "default");
+        assert!(profile.configs.is_empty());
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "default");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::price::{PriceContext, PriceProvider};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_sdk::account::Account;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+/// The token a pool prices its base token in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolQuote {
+    /// A USD stablecoin such as USDC.
+    Usd,
+    /// Wrapped SOL, converted to USD through the SOL pool.
+    Sol,
+}
+
+/// A constant-product liquidity pool, read through the token accounts holding its reserves.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PoolConfig {
+    /// Token account holding the base reserve, ORE for the ORE pools.
+    pub base_vault: String,
+    /// Token account holding the quote reserve.
+    pub quote_vault: String,
+    pub quote: PoolQuote,
+}
+
+/// The reserves of a pool, in whole tokens.
+struct Reserves {
+    base: f64,
+    quote: f64,
+}
+
+impl PoolConfig {
+    /// Reads both vaults of the pool in one request. The decimals of their mints are
+    /// looked up on first use and kept in `decimals`.
+    async fn reserves(
+        &self,
+        rpc_client: &RpcClient,
+        decimals: &Mutex<HashMap<Pubkey, u8>>,
+    ) -> Result<Reserves, String> {
+        let vaults = [
+            vault_address(&self.base_vault)?,
+            vault_address(&self.quote_vault)?,
+        ];
+        let accounts = rpc_client
+            .get_multiple_accounts(&vaults)
+            .await
+            .map_err(|e| format!("Failed to read vaults: {}", e))?;
+        let base = token_account(accounts.first(), &self.base_vault)?;
+        let quote = token_account(accounts.get(1), &self.quote_vault)?;
+        let missing: Vec<Pubkey> = {
+            let known = decimals.lock().unwrap();
+            [base.mint, quote.mint]
+                .into_iter()
+                .filter(|mint| !known.contains_key(mint))
+                .collect()
+        };
+        if !missing.is_empty() {
+            let mints = rpc_client
+                .get_multiple_accounts(&missing)
+                .await
+                .map_err(|e| format!("Failed to read mints: {}", e))?;
+            let mut known = decimals.lock().unwrap();
+            for (address, mint) in missing.iter().zip(mints) {
+                let mint = mint
+                    .and_then(|m| spl_token::state::Mint::unpack(&m.data).ok())
+                    .ok_or_else(|| format!("Invalid mint {}", address))?;
+                known.insert(*address, mint.decimals);
+            }
+        }
+        let known = decimals.lock().unwrap();
+        let ui_amount = |account: &spl_token::state::Account| {
+            account.amount as f64 / 10f64.powi(known[&account.mint] as i32)
+        };
+        Ok(Reserves {
+            base: ui_amount(&base),
+            quote: ui_amount(&quote),
+        })
+    }
+}
+
+fn vault_address(vault: &str) -> Result<Pubkey, String> {
+    Pubkey::from_str(vault).map_err(|_| format!("Invalid vault address: {}", vault))
+}
+
+fn token_account(
+    account: Option<&Option<Account>>,
+    vault: &str,
+) -> Result<spl_token::state::Account, String> {
+    let account = account
+        .and_then(Option::as_ref)
+        .ok_or_else(|| format!("Vault {} not found", vault))?;
+    spl_token::state::Account::unpack(&account.data)
+        .map_err(|_| format!("Invalid token account of vault {}", vault))
+}
+
+/// Prices ORE from the reserves of on-chain pools, needing no price API.
+/// Of several pools, the one with the deepest quote reserve sets the price.
+pub struct OnChainPools {
+    pools: Vec<PoolConfig>,
+    /// A SOL/USD pool pricing the pools quoted in SOL.
+    sol_pool: Option<PoolConfig>,
+    /// Decimals of the mints of the vaults read so far.
+    decimals: Mutex<HashMap<Pubkey, u8>>,
+}
+
+impl OnChainPools {
+    pub fn new(pools: Vec<PoolConfig>, sol_pool: Option<PoolConfig>) -> Self {
+        OnChainPools {
+            pools,
+            sol_pool,
+            decimals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    async fn sol_price(&self, rpc_client: &RpcClient) -> Result<f64, String> {
+        let pool = self
+            .sol_pool
+            .as_ref()
+            .ok_or_else(|| "No SOL pool to price SOL quoted pools".to_string())?;
+        let reserves = pool.reserves(rpc_client, &self.decimals).await?;
+        price_of(&reserves)
+    }
+}
+
+/// The price of the base token in the quote token.
+fn price_of(reserves: &Reserves) -> Result<f64, String> {
+    if reserves.base > 0.0 {
+        Ok(reserves.quote / reserves.base)
+    } else {
+        Err("Empty pool".to_string())
+    }
+}
+
+#[async_trait]
+impl PriceProvider for OnChainPools {
+    fn name(&self) -> &'static str {
+        "On-chain pools"
+    }
+
+    async fn price(&self, context: &PriceContext) -> Result<f64, String> {
+        let rpc_client = context
+            .rpc_client
+            .as_ref()
+            .ok_or_else(|| "No mainnet-beta endpoint to read pools from".to_string())?;
+        if self.pools.is_empty() {
+            return Err("No pools configured".to_string());
+        }
+        let mut sol_price = None;
+        let mut best: Option<(f64, f64)> = None;
+        let mut errors = vec![];
+        for pool in &self.pools {
+            let reserves = match pool.reserves(rpc_client, &self.decimals).await {
+                Ok(reserves) => reserves,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            // Convert SOL quotes to USD, looking the SOL price up once
+            let quote_usd = match pool.quote {
+                PoolQuote::Usd => 1.0,
+                PoolQuote::Sol => {
+                    if sol_price.is_none() {
+                        let result = self.sol_price(rpc_client).await;
+                        if let Err(e) = &result {
+                            errors.push(e.clone());
+                        }
+                        sol_price = Some(result.ok());
+                    }
+                    match sol_price.flatten() {
+                        Some(price) => price,
+                        None => continue,
+                    }
+                }
+            };
+            let price = match price_of(&reserves) {
+                Ok(price) => price * quote_usd,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            let depth = reserves.quote * quote_usd;
+            if best.map_or(true, |(_, best_depth)| depth > best_depth) {
+                best = Some((price, depth));
+            }
+        }
+        best.map(|(price, _)| price)
+            .ok_or_else(|| errors.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spl_token::state::AccountState;
+
+    #[test]
+    fn price_is_the_ratio_of_the_reserves() {
+        let reserves = Reserves {
+            base: 4.0,
+            quote: 10.0,
+        };
+        assert_eq!(price_of(&reserves), Ok(2.5));
+        let empty = Reserves {
+            base: 0.0,
+            quote: 10.0,
+        };
+        assert!(price_of(&empty).is_err());
+    }
+
+    #[test]
+    fn vaults_must_be_initialized_token_accounts() {
+        let mint = Pubkey::new_unique();
+        let vault = spl_token::state::Account {
+            mint,
+            amount: 42,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(vault, &mut data).unwrap();
+        let account = Some(Account {
+            data,
+            ..Default::default()
+        });
+        let parsed = token_account(Some(&account), "vault").unwrap();
+        assert_eq!((parsed.mint, parsed.amount), (mint, 42));
+
+        assert!(token_account(Some(&None), "vault").is_err());
+        assert!(token_account(None, "vault").is_err());
+        let garbage = Some(Account::default());
+        assert!(token_account(Some(&garbage), "vault").is_err());
+        assert!(vault_address("not a pubkey").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{balance::MinerStatus, history::PricePoint};
+use std::fmt;
+use std::ops::AddAssign;
+
+/// The assets held by a miner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Asset {
+    /// SOL in the miner's wallet.
+    Sol,
+    /// ORE in the miner's wallet.
+    WalletOre,
+    /// ORE staked in the miner's proof.
+    StakedOre,
+    /// Legacy v1 ORE in the miner's wallet, not yet upgraded.
+    LegacyOre,
+}
+
+impl Asset {
+    pub const ALL: [Asset; 4] = [
+        Asset::Sol,
+        Asset::WalletOre,
+        Asset::StakedOre,
+        Asset::LegacyOre,
+    ];
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Asset::Sol => "SOL",
+                Asset::WalletOre => "Wallet ORE",
+                Asset::StakedOre => "Staked ORE",
+                Asset::LegacyOre => "Legacy v1 ORE",
+            }
+        )
+    }
+}
+
+/// The prices of the assets in the display currency.
+#[derive(Debug, Clone, Copy)]
+pub struct Prices {
+    pub ore: f64,
+    /// Unknown when the SOL price could not be looked up.
+    pub sol: Option<f64>,
+}
+
+impl Prices {
+    pub fn from_point(point: &PricePoint) -> Self {
+        Prices {
+            ore: point.ore,
+            sol: point.sol,
+        }
+    }
+
+    /// The price of one unit of an asset. Legacy ORE upgrades one to one, so it is
+    /// priced as ORE.
+    pub fn of(&self, asset: Asset) -> Option<f64> {
+        match asset {
+            Asset::Sol => self.sol,
+            Asset::WalletOre | Asset::StakedOre | Asset::LegacyOre => Some(self.ore),
+        }
+    }
+}
+
+/// The amounts of every asset held by one miner or the whole fleet.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Holdings {
+    pub sol: f64,
+    pub wallet_ore: f64,
+    pub staked_ore: f64,
+    pub legacy_ore: f64,
+}
+
+impl Holdings {
+    pub fn from_status(status: &MinerStatus) -> Self {
+        Holdings {
+            sol: status.sol_balance,
+            wallet_ore: status.balance.parse::<f64>().unwrap_or(0.0),
+            staked_ore: status.stake.parse::<f64>().unwrap_or(0.0),
+            legacy_ore: status.legacy_balance,
+        }
+    }
+
+    pub fn amount(&self, asset: Asset) -> f64 {
+        match asset {
+            Asset::Sol => self.sol,
+            Asset::WalletOre => self.wallet_ore,
+            Asset::StakedOre => self.staked_ore,
+            Asset::LegacyOre => self.legacy_ore,
+        }
+    }
+
+    /// The value of one asset, or `None` while its price is unknown.
+    pub fn value_of(&self, asset: Asset, prices: &Prices) -> Option<f64> {
+        prices.of(asset).map(|price| self.amount(asset) * price)
+    }
+
+    /// The value of every asset whose price is known.
+    pub fn value(&self, prices: &Prices) -> f64 {
+        Asset::ALL
+            .iter()
+            .filter_map(|asset| self.value_of(*asset, prices))
+            .sum()
+    }
+
+    /// The share of an asset in the total value, between 0 and 1.
+    pub fn share(&self, asset: Asset, prices: &Prices) -> Option<f64> {
+        let total = self.value(prices);
+        self.value_of(asset, prices)
+            .filter(|_| total > 0.0)
+            .map(|value| value / total)
+    }
+
+    /// How much the value of the holdings changed between two sets of prices,
+    /// absolutely and relatively. Assets priced in only one set are left out.
+    pub fn change(&self, now: &Prices, before: &Prices) -> Option<(f64, f64)> {
+        let (mut current, mut previous) = (0.0, 0.0);
+        for asset in Asset::ALL {
+            if let (Some(value), Some(previous_value)) =
+                (self.value_of(asset, now), self.value_of(asset, before))
+            {
+                current += value;
+                previous += previous_value;
+            }
+        }
+        (previous > 0.0).then(|| (current - previous, (current - previous) / previous))
+    }
+}
+
+impl AddAssign for Holdings {
+    fn add_assign(&mut self, other: Holdings) {
+        self.sol += other.sol;
+        self.wallet_ore += other.wallet_ore;
+        self.staked_ore += other.staked_ore;
+        self.legacy_ore += other.legacy_ore;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holdings() -> Holdings {
+        Holdings {
+            sol: 2.0,
+            wallet_ore: 1.0,
+            staked_ore: 3.0,
+            legacy_ore: 0.5,
+        }
+    }
+
+    #[test]
+    fn holdings_are_valued_at_the_asset_prices() {
+        let prices = Prices {
+            ore: 10.0,
+            sol: Some(100.0),
+        };
+        let holdings = holdings();
+        assert_eq!(holdings.value_of(Asset::LegacyOre, &prices), Some(5.0));
+        assert_eq!(holdings.value(&prices), 245.0);
+        assert_eq!(holdings.share(Asset::Sol, &prices), Some(200.0 / 245.0));
+    }
+
+    #[test]
+    fn assets_without_a_price_are_left_out() {
+        let prices = Prices {
+            ore: 10.0,
+            sol: None,
+        };
+        let holdings = holdings();
+        assert_eq!(holdings.value_of(Asset::Sol, &prices), None);
+        assert_eq!(holdings.value(&prices), 45.0);
+        assert_eq!(Holdings::default().share(Asset::StakedOre, &prices), None);
+    }
+
+    #[test]
+    fn change_compares_assets_priced_both_times() {
+        let now = Prices {
+            ore: 12.0,
+            sol: Some(100.0),
+        };
+        let before = Prices {
+            ore: 10.0,
+            sol: None,
+        };
+        let (absolute, relative) = holdings().change(&now, &before).unwrap();
+        assert_eq!(absolute, 9.0);
+        assert_eq!(relative, 0.2);
+        assert!(Holdings::default().change(&now, &before).is_none());
+    }
+
+    #[test]
+    fn fleet_holdings_add_up() {
+        let mut fleet = holdings();
+        fleet += holdings();
+        assert_eq!(fleet.amount(Asset::Sol), 4.0);
+        assert_eq!(fleet.amount(Asset::LegacyOre), 1.0);
+    }
+}
@@ -1,10 +1,136 @@
-use crate::consts::{ORE_TOKEN_ID, USD_CURRENCY};
+use crate::consts::{
+    CRYPTO_PRECISION, FIAT_PRECISION, ORE_TOKEN_ID, PRICE_QUORUM, PRICE_TIMEOUT_SECONDS,
+    PRICE_TOLERANCE_SECONDS, SOL_TOKEN_ID, USD_CURRENCY,
+};
+use crate::history::PricePoint;
+use crate::pool_price::{OnChainPools, PoolConfig};
+use crate::utils::round_dp;
+use async_trait::async_trait;
+use ore_api::consts::MINT_ADDRESS;
 use reqwest::{Client, Error};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
 use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+/// What providers may use to look up a price.
+#[derive(Default)]
+pub struct PriceContext {
+    /// The RPC client of an account, for reading on-chain prices.
+    pub rpc_client: Option<Arc<RpcClient>>,
+    /// The currency the quote is converted to.
+    pub currency: Currency,
+}
+
+/// A currency values are shown in, by its CoinGecko code, or SOL.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Currency(String);
+
+impl Currency {
+    /// Currencies offered in the currency list. Others can be set in the config.
+    pub const COMMON: [&'static str; 12] = [
+        "usd", "eur", "gbp", "jpy", "cny", "krw", "inr", "cad", "aud", "chf", "brl", "sol",
+    ];
+
+    pub fn new(code: &str) -> Self {
+        Currency(code.trim().to_lowercase())
+    }
+
+    pub fn common() -> Vec<Currency> {
+        Currency::COMMON
+            .iter()
+            .map(|code| Currency::new(code))
+            .collect()
+    }
+
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_usd(&self) -> bool {
+        USD_CURRENCY == self.0
+    }
+
+    pub fn is_sol(&self) -> bool {
+        "sol" == self.0
+    }
+
+    /// The symbol written before amounts, or the upper case code when there is none.
+    pub fn symbol(&self) -> String {
+        match self.0.as_str() {
+            "usd" => "$",
+            "eur" => "€",
+            "gbp" => "£",
+            "jpy" | "cny" => "¥",
+            "krw" => "₩",
+            "inr" => "₹",
+            "rub" => "₽",
+            "cad" => "C$",
+            "aud" => "A$",
+            "brl" => "R$",
+            "sol" => "◎",
+            "btc" => "₿",
+            "eth" => "Ξ",
+            code => return format!("{} ", code.to_uppercase()),
+        }
+        .to_string()
+    }
+
+    /// Decimal places amounts are rounded to.
+    pub fn precision(&self) -> u8 {
+        match self.0.as_str() {
+            "sol" | "btc" | "eth" => CRYPTO_PRECISION,
+            _ => FIAT_PRECISION,
+        }
+    }
+
+    /// Formats an amount of the currency with its symbol.
+    pub fn format(&self, amount: f64) -> String {
+        format!("{}{}", self.symbol(), round_dp(amount, self.precision()))
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::new(USD_CURRENCY)
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0.to_uppercase())
+    }
+}
+
+/// A source of the USD price of ORE.
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn price(&self, context: &PriceContext) -> Result<f64, String>;
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .pool_max_idle_per_host(10) // Set maximum number of idle connections per host
+        .timeout(Duration::from_secs(PRICE_TIMEOUT_SECONDS)) // Set a request timeout
+        .build()
+        .unwrap()
+}
+
+async fn get_json<R: DeserializeOwned>(client: &Client, url: &str) -> Result<R, Error> {
+    client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+}
+
 pub struct CoinGecko {
     host: &'static str,
     client: Client,
@@ -20,35 +146,561 @@ impl CoinGecko {
     pub fn new(host: &'static str) -> Self {
         CoinGecko {
             host,
-            client: {
-                Client::builder()
-                    .pool_max_idle_per_host(10) // Set maximum number of idle connections per host
-                    .timeout(Duration::from_secs(10)) // Set a request timeout
-                    .build()
-                    .unwrap()
-            },
+            client: http_client(),
         }
     }
 
-    pub async fn get(&self) -> Result<HashMap<String, Price>, Error> {
+    /// Gets the prices of coins by id in each of the currencies.
+    pub async fn get(
+        &self,
+        ids: &[&str],
+        currencies: &[&str],
+    ) -> Result<HashMap<String, Price>, Error> {
         let req = format!(
             "/simple/price?ids={}&vs_currencies={}",
-            ORE_TOKEN_ID, USD_CURRENCY
+            ids.join(","),
+            currencies.join(",")
         );
         self.request(&req).await
     }
 
+    /// Gets the past prices of a coin in a currency over a number of days, hourly
+    /// for up to 90 days.
+    pub async fn market_chart(
+        &self,
+        id: &str,
+        currency: &str,
+        days: u32,
+    ) -> Result<Vec<(i64, f64)>, Error> {
+        let req = format!(
+            "/coins/{}/market_chart?vs_currency={}&days={}",
+            id, currency, days
+        );
+        let chart: MarketChart = self.request(&req).await?;
+        Ok(chart
+            .prices
+            .into_iter()
+            .map(|(millis, price)| (millis as i64 / 1000, price))
+            .collect())
+    }
+
     async fn request<R: DeserializeOwned>(&self, endpoint: &str) -> Result<R, Error> {
-        self.client
-            .get(format!("{host}/{ep}", host = self.host, ep = endpoint))
-            .send()
-            .await?
-            .json()
+        get_json(
+            &self.client,
+            &format!("{host}/{ep}", host = self.host, ep = endpoint),
+        )
+        .await
+    }
+}
+
+/// The price of a coin by currency code.
+pub type Price = HashMap<String, f64>;
+
+#[derive(Deserialize, Debug)]
+struct MarketChart {
+    /// Unix timestamps in milliseconds and prices, oldest first.
+    prices: Vec<(f64, f64)>,
+}
+
+#[async_trait]
+impl PriceProvider for CoinGecko {
+    fn name(&self) -> &'static str {
+        "CoinGecko"
+    }
+
+    async fn price(&self, _context: &PriceContext) -> Result<f64, String> {
+        let prices = self
+            .get(&[ORE_TOKEN_ID], &[USD_CURRENCY])
+            .await
+            .map_err(|e| e.to_string())?;
+        #[cfg(debug_assertions)]
+        {
+            println!("Price response: {:?}", prices);
+        }
+        prices
+            .get(ORE_TOKEN_ID)
+            .and_then(|p| p.get(USD_CURRENCY))
+            .copied()
+            .ok_or_else(|| "No ORE price in the response".to_string())
+    }
+}
+
+/// The price API of the Jupiter aggregator.
+pub struct Jupiter {
+    client: Client,
+}
+
+impl Default for Jupiter {
+    fn default() -> Self {
+        Jupiter {
+            client: http_client(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct JupiterResponse {
+    data: HashMap<String, Option<JupiterPrice>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct JupiterPrice {
+    price: String,
+}
+
+#[async_trait]
+impl PriceProvider for Jupiter {
+    fn name(&self) -> &'static str {
+        "Jupiter"
+    }
+
+    async fn price(&self, _context: &PriceContext) -> Result<f64, String> {
+        let mint = MINT_ADDRESS.to_string();
+        let url = format!("https://api.jup.ag/price/v2?ids={}", mint);
+        let response: JupiterResponse = get_json(&self.client, &url)
+            .await
+            .map_err(|e| e.to_string())?;
+        response
+            .data
+            .get(&mint)
+            .and_then(|p| p.as_ref())
+            .and_then(|p| p.price.parse::<f64>().ok())
+            .ok_or_else(|| "No ORE price in the response".to_string())
+    }
+}
+
+/// The pair data of DexScreener, priced by the most liquid ORE pair.
+pub struct DexScreener {
+    client: Client,
+}
+
+impl Default for DexScreener {
+    fn default() -> Self {
+        DexScreener {
+            client: http_client(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct DexScreenerResponse {
+    pairs: Option<Vec<DexScreenerPair>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct DexScreenerPair {
+    base_token: DexScreenerToken,
+    price_usd: Option<String>,
+    liquidity: Option<DexScreenerLiquidity>,
+}
+
+#[derive(Deserialize, Debug)]
+struct DexScreenerToken {
+    address: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct DexScreenerLiquidity {
+    usd: Option<f64>,
+}
+
+#[async_trait]
+impl PriceProvider for DexScreener {
+    fn name(&self) -> &'static str {
+        "DexScreener"
+    }
+
+    async fn price(&self, _context: &PriceContext) -> Result<f64, String> {
+        let mint = MINT_ADDRESS.to_string();
+        let url = format!("https://api.dexscreener.com/latest/dex/tokens/{}", mint);
+        let response: DexScreenerResponse = get_json(&self.client, &url)
             .await
+            .map_err(|e| e.to_string())?;
+        // Pairs quoting ORE against another token carry the price of that token
+        response
+            .pairs
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|p| p.base_token.address == mint)
+            .filter_map(|p| {
+                let price = p.price_usd?.parse::<f64>().ok()?;
+                let liquidity = p.liquidity.and_then(|l| l.usd).unwrap_or(0.0);
+                Some((price, liquidity))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(price, _)| price)
+            .ok_or_else(|| "No ORE pair in the response".to_string())
+    }
+}
+
+/// The price sources that can be configured.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    CoinGecko,
+    Jupiter,
+    DexScreener,
+    /// The reserves of the configured pools.
+    OnChain,
+}
+
+impl PriceSource {
+    fn provider(&self, config: &PriceConfig) -> Arc<dyn PriceProvider> {
+        match self {
+            PriceSource::CoinGecko => Arc::new(CoinGecko::default()),
+            PriceSource::Jupiter => Arc::new(Jupiter::default()),
+            PriceSource::DexScreener => Arc::new(DexScreener::default()),
+            PriceSource::OnChain => Arc::new(OnChainPools::new(
+                config.pools.clone(),
+                config.sol_pool.clone(),
+            )),
+        }
     }
 }
 
+/// Which sources the price is taken from.
 #[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Price {
-    pub usd: Option<f64>,
+#[serde(default)]
+pub struct PriceConfig {
+    /// Sources in the order they are asked.
+    pub sources: Vec<PriceSource>,
+    /// Responding sources the price is the median of.
+    pub quorum: usize,
+    /// ORE pools the on-chain price is read from.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pools: Vec<PoolConfig>,
+    /// A SOL/USD pool pricing the ORE pools quoted in SOL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sol_pool: Option<PoolConfig>,
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        PriceConfig {
+            sources: vec![
+                PriceSource::CoinGecko,
+                PriceSource::Jupiter,
+                PriceSource::DexScreener,
+                PriceSource::OnChain,
+            ],
+            quorum: PRICE_QUORUM,
+            pools: vec![],
+            sol_pool: None,
+        }
+    }
+}
+
+/// A price and where it came from.
+#[derive(Debug, Clone)]
+pub struct PriceQuote {
+    /// The price of ORE in the currency.
+    pub price: f64,
+    /// The price of SOL in the currency, if it could be looked up.
+    pub sol_price: Option<f64>,
+    pub currency: Currency,
+    /// Names of the sources that responded.
+    pub sources: Vec<&'static str>,
+    /// Unix timestamp of the quote.
+    pub timestamp: i64,
+    /// Whether the exchange rate is the last known one, CoinGecko being unreachable.
+    pub stale_rate: bool,
+}
+
+impl fmt::Display for PriceQuote {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.sources.join(", "))
+    }
+}
+
+/// Asks price sources in priority order, falling back to the next one on errors.
+pub struct PriceFeed {
+    providers: Vec<Arc<dyn PriceProvider>>,
+    quorum: usize,
+    /// Converts USD prices to other currencies.
+    exchange: CoinGecko,
+    /// The last exchange rate of each currency, used while CoinGecko can't be reached.
+    rates: Mutex<HashMap<String, f64>>,
+}
+
+impl PriceFeed {
+    pub fn new(config: &PriceConfig) -> Self {
+        PriceFeed {
+            providers: config.sources.iter().map(|s| s.provider(config)).collect(),
+            quorum: config.quorum.max(1),
+            exchange: CoinGecko::default(),
+            rates: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps a fetched exchange rate, or falls back to the last one kept for the
+    /// currency. Returns the rate and whether it is a fallback.
+    fn remember_rate(
+        &self,
+        currency: &Currency,
+        rate: Result<f64, String>,
+    ) -> Result<(f64, bool), String> {
+        let mut rates = self.rates.lock().unwrap();
+        match rate {
+            Ok(rate) => {
+                rates.insert(currency.code().to_string(), rate);
+                Ok((rate, false))
+            }
+            Err(e) => rates
+                .get(currency.code())
+                .map(|rate| (*rate, true))
+                .ok_or(e),
+        }
+    }
+
+    /// The price of SOL in USD and in a currency, which also gives the exchange rate.
+    async fn sol_prices(&self, currency: &Currency) -> Result<(f64, f64), String> {
+        let currencies: &[&str] = if currency.is_sol() || currency.is_usd() {
+            &[USD_CURRENCY]
+        } else {
+            &[USD_CURRENCY, currency.code()]
+        };
+        let prices = self
+            .exchange
+            .get(&[SOL_TOKEN_ID], currencies)
+            .await
+            .map_err(|e| format!("Failed to get the {} exchange rate: {}", currency, e))?;
+        let sol = prices.get(SOL_TOKEN_ID);
+        let usd = sol
+            .and_then(|p| p.get(USD_CURRENCY))
+            .copied()
+            .unwrap_or(0.0);
+        let price = if currency.is_sol() {
+            Some(1.0)
+        } else {
+            sol.and_then(|p| p.get(currency.code())).copied()
+        };
+        match price {
+            Some(price) if usd > 0.0 => Ok((usd, price)),
+            _ => Err(format!("No {} exchange rate", currency)),
+        }
+    }
+
+    /// Quotes the median USD price of the first `quorum` sources that respond,
+    /// converted to the currency of the context.
+    pub async fn quote(&self, context: &PriceContext) -> Result<PriceQuote, String> {
+        let mut prices = vec![];
+        let mut sources = vec![];
+        let mut errors = vec![];
+        for provider in &self.providers {
+            match provider.price(context).await {
+                Ok(price) if price > 0.0 => {
+                    prices.push(price);
+                    sources.push(provider.name());
+                }
+                Ok(_) => errors.push(format!("{}: no price", provider.name())),
+                Err(e) => errors.push(format!("{}: {}", provider.name(), e)),
+            }
+            if prices.len() >= self.quorum {
+                break;
+            }
+        }
+        if prices.is_empty() {
+            return Err(format!("No price source responded ({})", errors.join("; ")));
+        }
+        // USD prices need no conversion, so they are quoted even without the SOL price
+        let sol_prices = self.sol_prices(&context.currency).await;
+        let rate = match &sol_prices {
+            Ok((usd, price)) => Ok(price / usd),
+            Err(_) if context.currency.is_usd() => Ok(1.0),
+            Err(e) => Err(e.clone()),
+        };
+        let (rate, stale_rate) = self.remember_rate(&context.currency, rate)?;
+        Ok(PriceQuote {
+            price: median(&mut prices) * rate,
+            sol_price: sol_prices.ok().map(|(_, price)| price),
+            currency: context.currency.clone(),
+            sources,
+            timestamp: chrono::Utc::now().timestamp(),
+            stale_rate,
+        })
+    }
+}
+
+/// Gets the past prices of ORE and SOL in a currency from CoinGecko's market charts.
+/// CoinGecko has no SOL quotes, so prices in SOL are derived from the USD charts.
+pub async fn backfill_prices(currency: Currency, days: u32) -> Result<Vec<PricePoint>, String> {
+    let coingecko = CoinGecko::default();
+    let code = if currency.is_sol() {
+        USD_CURRENCY
+    } else {
+        currency.code()
+    };
+    let ore = coingecko
+        .market_chart(ORE_TOKEN_ID, code, days)
+        .await
+        .map_err(|e| format!("Failed to get the ORE market chart: {}", e))?;
+    let sol = coingecko
+        .market_chart(SOL_TOKEN_ID, code, days)
+        .await
+        .map_err(|e| format!("Failed to get the SOL market chart: {}", e))?;
+    Ok(ore
+        .into_iter()
+        .filter_map(|(timestamp, price)| {
+            // Pair every ORE price with the SOL price closest in time
+            let position = sol.partition_point(|(t, _)| *t < timestamp);
+            let sol_price = [position.checked_sub(1), Some(position)]
+                .into_iter()
+                .flatten()
+                .filter_map(|i| sol.get(i))
+                .min_by_key(|(t, _)| (t - timestamp).abs())
+                .map(|(_, p)| *p);
+            let point = if currency.is_sol() {
+                let sol_usd = sol_price.filter(|p| *p > 0.0)?;
+                PricePoint {
+                    timestamp,
+                    currency: currency.code().to_string(),
+                    ore: price / sol_usd,
+                    sol: Some(1.0),
+                }
+            } else {
+                PricePoint {
+                    timestamp,
+                    currency: currency.code().to_string(),
+                    ore: price,
+                    sol: sol_price,
+                }
+            };
+            Some(point)
+        })
+        .collect())
+}
+
+/// The time ranges between `from` and `to` with no stored price within the tolerance,
+/// given prices sorted by time.
+pub fn price_gaps(points: &[PricePoint], from: i64, to: i64) -> Vec<(i64, i64)> {
+    let times = points
+        .iter()
+        .map(|p| p.timestamp)
+        .filter(|t| *t > from && *t < to);
+    let bounds: Vec<i64> = std::iter::once(from)
+        .chain(times)
+        .chain(std::iter::once(to))
+        .collect();
+    bounds
+        .windows(2)
+        .filter(|pair| pair[1] - pair[0] > PRICE_TOLERANCE_SECONDS)
+        .map(|pair| (pair[0], pair[1]))
+        .collect()
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let middle = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        assert_eq!(median(&mut [3.0]), 3.0);
+        assert_eq!(median(&mut [5.0, 1.0, 3.0]), 3.0);
+        assert_eq!(median(&mut [4.0, 1.0, 3.0, 2.0]), 2.5);
+    }
+
+    #[test]
+    fn median_ignores_outliers() {
+        assert_eq!(median(&mut [1.0, 1000.0, 1.1, 0.9, 1.05]), 1.05);
+    }
+
+    #[test]
+    fn missing_price_config_fields_take_their_defaults() {
+        let config: PriceConfig = toml::from_str("quorum = 1").unwrap();
+        assert_eq!(config.quorum, 1);
+        assert_eq!(config.sources, PriceConfig::default().sources);
+    }
+
+    #[test]
+    fn currencies_are_formatted_with_their_symbol() {
+        assert_eq!(Currency::new(" EUR ").code(), "eur");
+        assert_eq!(Currency::new("usd").format(1.5), "$1.5");
+        assert_eq!(Currency::new("sol").symbol(), "◎");
+        assert_eq!(Currency::new("chf").symbol(), "CHF ");
+        assert_eq!(Currency::new("chf").to_string(), "CHF");
+        assert!(Currency::default().is_usd());
+    }
+
+    #[test]
+    fn currency_is_stored_as_its_code() {
+        #[derive(Serialize, Deserialize)]
+        struct Config {
+            currency: Currency,
+        }
+        let config: Config = toml::from_str("currency = \"jpy\"").unwrap();
+        assert_eq!(config.currency, Currency::new("jpy"));
+        assert_eq!(
+            toml::to_string(&config).unwrap().trim(),
+            "currency = \"jpy\""
+        );
+    }
+
+    #[test]
+    fn last_exchange_rate_is_used_while_coingecko_is_unreachable() {
+        let feed = PriceFeed::new(&PriceConfig::default());
+        let eur = Currency::new("eur");
+        let unreachable = || Err("unreachable".to_string());
+        assert!(feed.remember_rate(&eur, unreachable()).is_err());
+        assert_eq!(feed.remember_rate(&eur, Ok(0.9)), Ok((0.9, false)));
+        assert_eq!(feed.remember_rate(&eur, unreachable()), Ok((0.9, true)));
+        assert!(feed
+            .remember_rate(&Currency::new("gbp"), unreachable())
+            .is_err());
+    }
+
+    fn point(timestamp: i64) -> PricePoint {
+        PricePoint {
+            timestamp,
+            currency: USD_CURRENCY.to_string(),
+            ore: 1.0,
+            sol: None,
+        }
+    }
+
+    #[test]
+    fn every_stretch_without_prices_is_a_gap() {
+        let hour = PRICE_TOLERANCE_SECONDS;
+        let points = [
+            point(hour / 2),
+            point(hour),
+            point(5 * hour),
+            point(5 * hour + 600),
+        ];
+        assert_eq!(
+            price_gaps(&points, 0, 8 * hour),
+            vec![(hour, 5 * hour), (5 * hour + 600, 8 * hour)]
+        );
+    }
+
+    #[test]
+    fn without_prices_the_whole_range_is_a_gap() {
+        assert_eq!(price_gaps(&[], 0, 86400), vec![(0, 86400)]);
+        // Prices outside the range are ignored
+        assert_eq!(
+            price_gaps(&[point(-10), point(90000)], 0, 86400),
+            vec![(0, 86400)]
+        );
+        assert!(price_gaps(&[], 0, PRICE_TOLERANCE_SECONDS).is_empty());
+    }
+
+    #[test]
+    fn quorum_is_at_least_one() {
+        let config = PriceConfig {
+            sources: vec![PriceSource::Jupiter],
+            quorum: 0,
+        };
+        let feed = PriceFeed::new(&config);
+        assert_eq!(feed.quorum, 1);
+        assert_eq!(feed.providers[0].name(), "Jupiter");
+    }
 }
@@ -0,0 +1,119 @@
+use crate::consts::{RPC_MAX_CONCURRENT_REQUESTS, RPC_REQUESTS_PER_SECOND};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// An RPC endpoint shared by all accounts using the same URL.
+/// Requests through it are limited in concurrency and rate, to stay within provider limits.
+pub struct Endpoint {
+    pub url: String,
+    pub rpc_client: Arc<RpcClient>,
+    semaphore: Semaphore,
+    /// The minimum spacing between two requests.
+    interval: Duration,
+    /// The earliest time the next request may start.
+    next_slot: Mutex<Instant>,
+}
+
+impl Endpoint {
+    pub fn new(url: String, max_concurrent: usize, requests_per_second: u32) -> Self {
+        let rpc_client = RpcClient::new_with_commitment(url.clone(), CommitmentConfig::confirmed());
+        Endpoint {
+            url,
+            rpc_client: Arc::new(rpc_client),
+            semaphore: Semaphore::new(max_concurrent),
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits for a free request slot and for the budget of one request. The slot is held
+    /// until the returned permit is dropped, and further requests made with it are paced
+    /// one by one with `pace`.
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("Endpoint semaphore is never closed");
+        self.pace().await;
+        permit
+    }
+
+    /// Waits for the budget of one request, so requests are spread over the rate limit
+    /// instead of sent in bursts.
+    pub async fn pace(&self) {
+        let wait = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.interval;
+            slot - now
+        };
+        tokio::time::sleep(wait).await;
+    }
+
+    /// Estimates how long the given number of requests take within the rate budget.
+    pub fn budget_time(&self, requests: u32) -> Duration {
+        self.interval * requests
+    }
+}
+
+/// Pools endpoints by URL, so accounts sharing an RPC provider share one client and one budget.
+#[derive(Default)]
+pub struct RpcPool {
+    endpoints: HashMap<String, Arc<Endpoint>>,
+}
+
+impl RpcPool {
+    /// Returns the endpoint for a URL, creating it on first use.
+    pub fn get(&mut self, url: &str) -> Arc<Endpoint> {
+        let endpoint = self.endpoints.entry(url.to_string()).or_insert_with(|| {
+            Arc::new(Endpoint::new(
+                url.to_string(),
+                RPC_MAX_CONCURRENT_REQUESTS,
+                RPC_REQUESTS_PER_SECOND,
+            ))
+        });
+        Arc::clone(endpoint)
+    }
+
+    pub fn endpoints(&self) -> impl Iterator<Item = &Arc<Endpoint>> {
+        self.endpoints.values()
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.endpoints.contains_key(url)
+    }
+
+    /// Drops the endpoints no account uses anymore.
+    pub fn retain_used<'a>(&mut self, urls: impl IntoIterator<Item = &'a str>) {
+        let used: Vec<&str> = urls.into_iter().collect();
+        self.endpoints.retain(|url, _| used.contains(&url.as_str()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accounts_on_the_same_url_share_an_endpoint() {
+        let mut pool = RpcPool::default();
+        let first = pool.get("http://localhost:8899");
+        assert!(Arc::ptr_eq(&first, &pool.get("http://localhost:8899")));
+        assert!(!Arc::ptr_eq(&first, &pool.get("http://localhost:8900")));
+
+        pool.retain_used(["http://localhost:8900"]);
+        assert!(!Arc::ptr_eq(&first, &pool.get("http://localhost:8899")));
+    }
+
+    #[test]
+    fn requests_are_spread_over_the_rate_limit() {
+        let endpoint = Endpoint::new("http://localhost:8899".to_string(), 1, 10);
+        assert_eq!(endpoint.budget_time(5), Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,329 @@
+use crate::{
+    claim::ClaimParams,
+    logic::{request_claim, request_stake, request_transfer},
+    miner::Miner,
+    stake::StakeParams,
+    transfer::TransferParams,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use solana_program::pubkey::Pubkey;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The operations a job can run at its scheduled times.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledAction {
+    /// Claims the staked ORE of the proof.
+    Claim,
+    /// Stakes the ORE of the miner's wallet.
+    Stake,
+    /// Transfers the ORE of the miner's wallet to another wallet.
+    Transfer,
+}
+
+impl ScheduledAction {
+    /// The name the action is stored under in the history.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduledAction::Claim => "claim",
+            ScheduledAction::Stake => "stake",
+            ScheduledAction::Transfer => "transfer",
+        }
+    }
+}
+
+impl FromStr for ScheduledAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "claim" => Ok(ScheduledAction::Claim),
+            "stake" => Ok(ScheduledAction::Stake),
+            "transfer" => Ok(ScheduledAction::Transfer),
+            _ => Err(format!("Unknown scheduled action: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for ScheduledAction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ScheduledAction::Claim => "Claim",
+                ScheduledAction::Stake => "Stake",
+                ScheduledAction::Transfer => "Transfer",
+            }
+        )
+    }
+}
+
+/// An operation run on a cron schedule.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScheduledJob {
+    pub name: String,
+    /// A cron expression, with or without the leading seconds field.
+    pub cron: String,
+    /// The IANA timezone the expression is read in.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    pub action: ScheduledAction,
+    /// Authorities of the miners the job runs for, every miner when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<String>,
+    /// ORE to move from every miner, everything when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<f64>,
+    /// Wallet claimed or transferred ORE is sent to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wallet_address: Option<String>,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+
+/// Parses a cron expression. Besides the six or seven fields of the `cron` crate,
+/// which start with seconds, the five fields of a crontab are accepted.
+pub fn parse_cron(expression: &str) -> Result<Schedule, String> {
+    let expression = expression.trim();
+    let expression = if expression.split_whitespace().count() == 5 {
+        format!("0 {}", expression)
+    } else {
+        expression.to_string()
+    };
+    Schedule::from_str(&expression)
+        .map_err(|e| format!("Invalid cron expression {}: {}", expression, e))
+}
+
+/// A scheduled job with its parsed timing.
+pub struct Job {
+    pub config: ScheduledJob,
+    /// The schedule and timezone, or why the job can't run.
+    timing: Result<(Schedule, Tz), String>,
+    /// Unix timestamp of the next run.
+    pub next_run: Option<i64>,
+}
+
+impl Job {
+    /// Parses a job, scheduling its first run after `now`. Runs missed while the
+    /// dashboard was closed are not caught up.
+    pub fn new(config: ScheduledJob, now: i64) -> Self {
+        let timing = Job::parse(&config);
+        let mut job = Job {
+            config,
+            timing,
+            next_run: None,
+        };
+        job.advance(now);
+        job
+    }
+
+    fn parse(config: &ScheduledJob) -> Result<(Schedule, Tz), String> {
+        let schedule = parse_cron(&config.cron)?;
+        let timezone = Tz::from_str(&config.timezone)
+            .map_err(|e| format!("Invalid timezone {}: {}", config.timezone, e))?;
+        match &config.wallet_address {
+            Some(wallet) => {
+                Pubkey::from_str(wallet)
+                    .map_err(|_| format!("Invalid wallet address: {}", wallet))?;
+            }
+            None if ScheduledAction::Transfer == config.action => {
+                return Err("A transfer needs a wallet_address".to_string());
+            }
+            None => {}
+        }
+        Ok((schedule, timezone))
+    }
+
+    /// Why the job can't run, if it can't.
+    pub fn error(&self) -> Option<&str> {
+        self.timing.as_ref().err().map(|e| e.as_str())
+    }
+
+    pub fn is_due(&self, now: i64) -> bool {
+        self.next_run.is_some_and(|next| next <= now)
+    }
+
+    /// Schedules the next run after `now`.
+    pub fn advance(&mut self, now: i64) {
+        self.next_run = self.upcoming(now, 1).first().map(|t| t.timestamp());
+    }
+
+    /// The next runs after `now`, in the job's timezone.
+    pub fn upcoming(&self, now: i64, count: usize) -> Vec<DateTime<Tz>> {
+        let Ok((schedule, timezone)) = &self.timing else {
+            return vec![];
+        };
+        let Some(now) = Utc.timestamp_opt(now, 0).single() else {
+            return vec![];
+        };
+        schedule
+            .after(&now.with_timezone(timezone))
+            .take(count)
+            .collect()
+    }
+
+    /// Whether the job runs for a miner.
+    pub fn targets(&self, authority: &Pubkey) -> bool {
+        self.config.accounts.is_empty()
+            || self
+                .config
+                .accounts
+                .iter()
+                .any(|a| a.trim() == authority.to_string())
+    }
+}
+
+/// A completed run of a scheduled job for one miner.
+#[derive(Debug, Clone)]
+pub struct ScheduleRun {
+    pub job: String,
+    pub authority: Pubkey,
+    /// Unix timestamp the run was scheduled for.
+    pub timestamp: i64,
+    pub action: ScheduledAction,
+    /// ORE the run was meant to move, everything when unset.
+    pub amount: Option<f64>,
+    pub succeeded: bool,
+    /// Why the run failed, if it did.
+    pub error: Option<String>,
+}
+
+impl ScheduleRun {
+    pub fn failed(job: &ScheduledJob, authority: Pubkey, timestamp: i64, error: String) -> Self {
+        ScheduleRun {
+            job: job.name.clone(),
+            authority,
+            timestamp,
+            action: job.action,
+            amount: job.amount,
+            succeeded: false,
+            error: Some(error),
+        }
+    }
+}
+
+/// Runs a job for one miner through its claim, stake or transfer operation.
+pub async fn run_job(
+    miner: Arc<Miner>,
+    job: ScheduledJob,
+    authority: Pubkey,
+    timestamp: i64,
+) -> ScheduleRun {
+    let result = match job.action {
+        ScheduledAction::Claim => {
+            let params = ClaimParams {
+                amount: job.amount,
+                wallet_address: job.wallet_address.clone(),
+            };
+            request_claim(miner, params).await
+        }
+        ScheduledAction::Stake => {
+            let params = StakeParams {
+                amount: job.amount,
+                sender: None,
+            };
+            request_stake(miner, params).await
+        }
+        ScheduledAction::Transfer => {
+            let params = TransferParams {
+                amount: job.amount,
+                wallet_address: job.wallet_address.clone().unwrap_or_default(),
+            };
+            request_transfer(miner, params).await
+        }
+    };
+    ScheduleRun {
+        job: job.name,
+        authority,
+        timestamp,
+        action: job.action,
+        amount: job.amount,
+        succeeded: result.is_ok(),
+        error: result.err(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(cron: &str, timezone: &str, action: ScheduledAction) -> ScheduledJob {
+        ScheduledJob {
+            name: "weekly".to_string(),
+            cron: cron.to_string(),
+            timezone: timezone.to_string(),
+            action,
+            accounts: vec![],
+            amount: None,
+            wallet_address: None,
+        }
+    }
+
+    #[test]
+    fn parse_cron_accepts_five_fields() {
+        let five = parse_cron("30 9 * * Mon").unwrap();
+        let six = parse_cron("0 30 9 * * Mon").unwrap();
+        let start = Utc.timestamp_opt(0, 0).unwrap();
+        let runs = |schedule: &Schedule| schedule.after(&start).take(3).collect::<Vec<_>>();
+        assert_eq!(runs(&five), runs(&six));
+        assert_eq!(runs(&five)[0].timestamp(), 4 * 86400 + 9 * 3600 + 30 * 60);
+    }
+
+    #[test]
+    fn parse_cron_accepts_seconds_and_years() {
+        assert!(parse_cron("  15 0 0 1 * * 2030 ").is_ok());
+    }
+
+    #[test]
+    fn parse_cron_rejects_invalid_expressions() {
+        assert!(parse_cron("").is_err());
+        assert!(parse_cron("every day").is_err());
+        assert!(parse_cron("0 61 * * *").is_err());
+    }
+
+    #[test]
+    fn jobs_run_in_their_timezone() {
+        // 1970-01-01 was a Thursday, and Tokyo is 9 hours ahead of UTC
+        let job = Job::new(job("0 9 * * *", "Asia/Tokyo", ScheduledAction::Claim), 0);
+        assert_eq!(job.error(), None);
+        assert_eq!(job.next_run, Some(86400));
+        assert!(!job.is_due(86399));
+        assert!(job.is_due(86400));
+    }
+
+    #[test]
+    fn advance_schedules_the_run_after_now() {
+        let mut job = Job::new(job("0 0 * * *", "UTC", ScheduledAction::Stake), 0);
+        assert_eq!(job.next_run, Some(86400));
+        job.advance(86400);
+        assert_eq!(job.next_run, Some(2 * 86400));
+    }
+
+    #[test]
+    fn invalid_jobs_never_run() {
+        let unknown = Job::new(job("0 0 * * *", "Mars/Olympus", ScheduledAction::Claim), 0);
+        assert!(unknown.error().is_some());
+        assert_eq!(unknown.next_run, None);
+
+        let transfer = Job::new(job("0 0 * * *", "UTC", ScheduledAction::Transfer), 0);
+        assert_eq!(transfer.error(), Some("A transfer needs a wallet_address"));
+        assert!(!transfer.is_due(i64::MAX));
+    }
+
+    #[test]
+    fn jobs_without_accounts_target_every_miner() {
+        let authority = Pubkey::new_unique();
+        let mut config = job("0 0 * * *", "UTC", ScheduledAction::Claim);
+        assert!(Job::new(config.clone(), 0).targets(&authority));
+        config.accounts = vec![format!(" {} ", authority)];
+        assert!(Job::new(config.clone(), 0).targets(&authority));
+        assert!(!Job::new(config, 0).targets(&Pubkey::new_unique()));
+    }
+}
@@ -10,12 +10,11 @@ use solana_program::{
 };
 use solana_rpc_client::spinner;
 use solana_sdk::{
-    commitment_config::CommitmentLevel,
     compute_budget::ComputeBudgetInstruction,
     signature::{Signature, Signer},
     transaction::Transaction,
 };
-use solana_transaction_status::{TransactionConfirmationStatus, UiTransactionEncoding};
+use solana_transaction_status::UiTransactionEncoding;
 
 use crate::miner::Miner;
 
@@ -42,18 +41,23 @@ impl Miner {
         skip_confirm: bool,
     ) -> ClientResult<Signature> {
         let progress_bar = spinner::new_progress_bar();
-        let signer = self.signer();
+        let signer = self.try_signer().map_err(|e| ClientError {
+            request: None,
+            kind: ClientErrorKind::Custom(e),
+        })?;
         let client = self.rpc_client.clone();
 
         // Return error, if balance is zero
         if let Ok(balance) = client.get_balance(&signer.pubkey()).await {
             if balance <= sol_to_lamports(MIN_SOL_BALANCE) {
-                panic!(
-                    "{} Insufficient balance: {} SOL\nPlease top up with at least {} SOL",
-                    "ERROR",
-                    lamports_to_sol(balance),
-                    MIN_SOL_BALANCE
-                );
+                return Err(ClientError {
+                    request: None,
+                    kind: ClientErrorKind::Custom(format!(
+                        "Insufficient balance: {} SOL. Please top up with at least {} SOL",
+                        lamports_to_sol(balance),
+                        MIN_SOL_BALANCE
+                    )),
+                });
             }
         }
 
@@ -76,7 +80,7 @@ impl Miner {
         // Build tx
         let send_cfg = RpcSendTransactionConfig {
             skip_preflight: true,
-            preflight_commitment: Some(CommitmentLevel::Confirmed),
+            preflight_commitment: Some(self.read_commitment.level()),
             encoding: Some(UiTransactionEncoding::Base64),
             max_retries: Some(RPC_RETRIES),
             min_context_slot: None,
@@ -85,12 +89,12 @@ impl Miner {
 
         // Sign tx
         let (hash, _slot) = client
-            .get_latest_blockhash_with_commitment(self.rpc_client.commitment())
-            .await
-            .unwrap();
+            .get_latest_blockhash_with_commitment(self.read_commitment.for_blockhash().config())
+            .await?;
         tx.sign(&[&signer], hash);
 
         // Submit tx
+        let commitment = self.confirm_commitment;
         let mut attempts = 0;
         loop {
             progress_bar.set_message(format!("Submitting transaction... (attempt {})", attempts));
@@ -104,7 +108,7 @@ impl Miner {
 
                     // Confirm the tx landed
                     for _ in 0..CONFIRM_RETRIES {
-                        std::thread::sleep(Duration::from_millis(CONFIRM_DELAY));
+                        tokio::time::sleep(Duration::from_millis(CONFIRM_DELAY)).await;
                         match client.get_signature_statuses(&[sig]).await {
                             Ok(signature_statuses) => {
                                 for status in signature_statuses.value {
@@ -116,19 +120,15 @@ impl Miner {
                                                 kind: ClientErrorKind::Custom(err.to_string()),
                                             });
                                         }
-                                        if let Some(confirmation) = status.confirmation_status {
-                                            match confirmation {
-                                                TransactionConfirmationStatus::Processed => {}
-                                                TransactionConfirmationStatus::Confirmed
-                                                | TransactionConfirmationStatus::Finalized => {
-                                                    progress_bar.finish_with_message(format!(
-                                                        "{} {}",
-                                                        "OK",
-                                                        sig
-                                                    ));
-                                                    return Ok(sig);
-                                                }
-                                            }
+                                        // Wait until the transaction is as settled as the
+                                        // account asks for
+                                        if status
+                                            .confirmation_status
+                                            .is_some_and(|c| commitment.is_reached(&c))
+                                        {
+                                            progress_bar
+                                                .finish_with_message(format!("{} {}", "OK", sig));
+                                            return Ok(sig);
                                         }
                                     }
                                 }
@@ -148,16 +148,12 @@ impl Miner {
 
                 // Handle submit errors
                 Err(err) => {
-                    progress_bar.set_message(format!(
-                        "{}: {}",
-                        "ERROR",
-                        err.kind().to_string()
-                    ));
+                    progress_bar.set_message(format!("{}: {}", "ERROR", err.kind().to_string()));
                 }
             }
 
             // Retry
-            std::thread::sleep(Duration::from_millis(GATEWAY_DELAY));
+            tokio::time::sleep(Duration::from_millis(GATEWAY_DELAY)).await;
             attempts += 1;
             if attempts > GATEWAY_RETRIES {
                 progress_bar.finish_with_message(format!("{}: Max retries", "ERROR"));
@@ -3,7 +3,7 @@ use crate::{
 };
 use ore_api::consts::MINT_ADDRESS;
 use solana_program::pubkey::Pubkey;
-use solana_sdk::signature::Signer;
+use solana_sdk::signature::{Signature, Signer};
 use std::str::FromStr;
 
 pub struct StakeParams {
@@ -14,10 +14,11 @@ pub struct StakeParams {
 }
 
 impl Miner {
-    pub async fn stake(&self, params: StakeParams) -> bool {
-        let signer = self.signer();
+    pub async fn stake(&self, params: StakeParams) -> Result<Signature, String> {
+        let signer = self.try_signer()?;
         let sender = match params.sender {
-            Some(sender) => Pubkey::from_str(&sender).expect("Failed to parse sender address"),
+            Some(sender) => Pubkey::from_str(&sender)
+                .map_err(|_| format!("Invalid sender address: {}", sender))?,
             None => signer.pubkey(),
         };
         // Get ATA
@@ -25,22 +26,20 @@ impl Miner {
             spl_associated_token_account::get_associated_token_address(&sender, &MINT_ADDRESS);
         // Get token account
         let Ok(Some(token_account)) = self.rpc_client.get_token_account(&beneficiary).await else {
-            println!("Failed to fetch token account");
-            return false;
+            return Err("Failed to fetch token account".to_string());
         };
         // Parse amount
         let amount: u64 = if let Some(amount) = params.amount {
             amount_f64_to_u64(amount)
         } else {
             u64::from_str(token_account.token_amount.amount.as_str())
-                .expect("Failed to parse token balance")
+                .map_err(|_| "Failed to parse token balance".to_string())?
         };
 
         // Send tx
         let ix = ore_api::instruction::stake(signer.pubkey(), beneficiary, amount);
         self.send_and_confirm(&[ix], ComputeBudget::Fixed(CU_LIMIT_CLAIM), false)
             .await
-            .ok();
-        true
+            .map_err(|e| format!("Stake failed: {}", e))
     }
 }
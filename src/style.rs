@@ -1,5 +1,5 @@
 use iced::widget::container;
-use iced::Theme;
+use iced::{border, Theme};
 pub fn pane_pop(theme: &Theme) -> container::Style {
     let palette = theme.extended_palette();
 
@@ -8,3 +8,15 @@ pub fn pane_pop(theme: &Theme) -> container::Style {
         ..Default::default()
     }
 }
+
+/// A small rounded label, e.g. the cluster of an account.
+pub fn badge(theme: &Theme) -> container::Style {
+    let palette = theme.extended_palette();
+
+    container::Style {
+        background: Some(palette.secondary.weak.color.into()),
+        text_color: Some(palette.secondary.weak.text),
+        border: border::rounded(4),
+        ..Default::default()
+    }
+}
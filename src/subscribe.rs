@@ -0,0 +1,324 @@
+use crate::{
+    balance::MinerStatus,
+    cluster::Commitment,
+    consts::{SUBSCRIPTION_CHANNEL_SIZE, SUBSCRIPTION_RETRY_MAX_SECONDS},
+    logic::Message,
+    rpc_pool::Endpoint,
+    utils::{parse_legacy_token_balance, parse_token_balance, proof_pubkey},
+};
+use iced::futures::{
+    channel::mpsc::Sender,
+    stream::{self, Stream, StreamExt},
+    SinkExt,
+};
+use ore_api::{
+    consts::{CONFIG_ADDRESS, MINT_ADDRESS, MINT_V1_ADDRESS},
+    state::{Config as GlobalConfig, Proof},
+};
+use ore_utils::AccountDeserialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_cli_config::Config;
+use solana_client::{
+    nonblocking::pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig,
+    rpc_request::MAX_MULTIPLE_ACCOUNTS,
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::account::Account;
+use spl_associated_token_account::get_associated_token_address;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The accounts watched for every miner.
+#[derive(Debug, Clone, Copy)]
+enum WatchedAccount {
+    Proof,
+    Token,
+    Wallet,
+    LegacyToken,
+}
+
+/// The account a notification belongs to, the config being shared by all miners.
+#[derive(Debug, Clone, Copy)]
+enum Watched {
+    Miner(usize, WatchedAccount),
+    Config,
+}
+
+/// The latest known state of the accounts backing a miner status.
+#[derive(Default)]
+struct MinerState {
+    proof: Option<Proof>,
+    token_balance: Option<String>,
+    lamports: u64,
+    legacy_balance: f64,
+}
+
+impl MinerState {
+    fn apply(&mut self, kind: WatchedAccount, account: Option<Account>) {
+        match kind {
+            WatchedAccount::Proof => {
+                self.proof = account.and_then(|a| Proof::try_from_bytes(&a.data).ok().copied());
+            }
+            WatchedAccount::Token => {
+                self.token_balance = account.and_then(|a| parse_token_balance(&a.data));
+            }
+            WatchedAccount::Wallet => {
+                self.lamports = account.map_or(0, |a| a.lamports);
+            }
+            WatchedAccount::LegacyToken => {
+                self.legacy_balance = account
+                    .and_then(|a| parse_legacy_token_balance(&a.data))
+                    .unwrap_or(0.0);
+            }
+        }
+    }
+
+    fn status(&self, top_balance: u64) -> Option<MinerStatus> {
+        let proof = self.proof.as_ref()?;
+        let balance = self
+            .token_balance
+            .clone()
+            .unwrap_or_else(|| "0".to_string());
+        Some(
+            MinerStatus::from_proof(proof, balance, top_balance)
+                .with_lamports(self.lamports)
+                .with_legacy_balance(self.legacy_balance),
+        )
+    }
+}
+
+/// The state of every miner watched on an endpoint.
+struct EndpointState {
+    miners: Vec<MinerState>,
+    top_balance: u64,
+}
+
+impl EndpointState {
+    fn new(miners: usize) -> Self {
+        EndpointState {
+            miners: (0..miners).map(|_| MinerState::default()).collect(),
+            top_balance: 0,
+        }
+    }
+
+    fn apply(&mut self, watched: Watched, account: Option<Account>) {
+        match watched {
+            Watched::Miner(index, kind) => self.miners[index].apply(kind, account),
+            Watched::Config => {
+                if let Some(config) =
+                    account.and_then(|a| GlobalConfig::try_from_bytes(&a.data).ok().copied())
+                {
+                    self.top_balance = config.top_balance;
+                }
+            }
+        }
+    }
+
+    /// The statuses changed by a notification: a new top balance changes every multiplier.
+    fn statuses(&self, watched: Watched) -> Vec<(usize, MinerStatus)> {
+        let indexes = match watched {
+            Watched::Miner(index, _) => index..index + 1,
+            Watched::Config => 0..self.miners.len(),
+        };
+        indexes
+            .filter_map(|index| Some((index, self.miners[index].status(self.top_balance)?)))
+            .collect()
+    }
+}
+
+/// The addresses watched for the given miners with the commitment they are read at,
+/// the global config first. The config only moves the multipliers, so it is read at
+/// the default commitment.
+fn watched_addresses(miners: &[(Pubkey, Commitment)]) -> Vec<(Watched, Pubkey, Commitment)> {
+    let mut watched = vec![(Watched::Config, CONFIG_ADDRESS, Commitment::default())];
+    for (index, (authority, commitment)) in miners.iter().enumerate() {
+        let accounts = [
+            (WatchedAccount::Proof, proof_pubkey(*authority)),
+            (
+                WatchedAccount::Token,
+                get_associated_token_address(authority, &MINT_ADDRESS),
+            ),
+            (WatchedAccount::Wallet, *authority),
+            (
+                WatchedAccount::LegacyToken,
+                get_associated_token_address(authority, &MINT_V1_ADDRESS),
+            ),
+        ];
+        for (kind, address) in accounts {
+            watched.push((Watched::Miner(index, kind), address, *commitment));
+        }
+    }
+    watched
+}
+
+/// Watches the proof, token accounts and wallet of every miner on an endpoint through
+/// `accountSubscribe`, all over a single websocket, reconnecting with an
+/// increasing delay whenever it is lost. Every miner is watched at its own commitment.
+pub fn watch_endpoint(
+    endpoint: Arc<Endpoint>,
+    miners: Vec<(Pubkey, Commitment)>,
+) -> impl Stream<Item = Message> {
+    iced::stream::channel(SUBSCRIPTION_CHANNEL_SIZE, move |mut output| async move {
+        let ws_url = Config::compute_websocket_url(&endpoint.url);
+        let mut delay = 1;
+        loop {
+            match watch(&endpoint, &ws_url, &miners, &mut output).await {
+                // The connection was established before it dropped, so retry quickly
+                Ok(_) => delay = 1,
+                Err(e) => eprintln!("Subscription to {} failed: {}", ws_url, e),
+            }
+            // Fall back to polling until the subscription is back
+            for (authority, _) in &miners {
+                let _ = output.send(Message::SubscriptionFailed(*authority)).await;
+            }
+            tokio::time::sleep(Duration::from_secs(delay)).await;
+            delay = (delay * 2).min(SUBSCRIPTION_RETRY_MAX_SECONDS);
+        }
+    })
+}
+
+async fn watch(
+    endpoint: &Endpoint,
+    ws_url: &str,
+    miners: &[(Pubkey, Commitment)],
+    output: &mut Sender<Message>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let pubsub = PubsubClient::new(ws_url).await?;
+    let watched = watched_addresses(miners);
+    let mut subscriptions = Vec::with_capacity(watched.len());
+    for (kind, address, commitment) in watched.iter().copied() {
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(commitment.config()),
+            ..Default::default()
+        };
+        let (updates, _) = pubsub.account_subscribe(&address, Some(config)).await?;
+        subscriptions.push(updates.map(move |response| (kind, response.value)).boxed());
+    }
+    for (authority, _) in miners {
+        let _ = output
+            .send(Message::SubscriptionConnected(*authority))
+            .await;
+    }
+
+    // Take a snapshot, so the statuses are complete before the first notification arrives.
+    // Accounts are read in chunks of the same commitment.
+    let mut state = EndpointState::new(miners.len());
+    for commitment in Commitment::ALL {
+        let group: Vec<(Watched, Pubkey)> = watched
+            .iter()
+            .filter(|(_, _, c)| *c == commitment)
+            .map(|(kind, address, _)| (*kind, *address))
+            .collect();
+        for chunk in group.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let addresses: Vec<Pubkey> = chunk.iter().map(|(_, address)| *address).collect();
+            let accounts = {
+                let _permit = endpoint.acquire().await;
+                endpoint
+                    .rpc_client
+                    .get_multiple_accounts_with_commitment(&addresses, commitment.config())
+                    .await?
+                    .value
+            };
+            for ((kind, _), account) in chunk.iter().zip(accounts) {
+                state.apply(*kind, account);
+            }
+        }
+    }
+    for (index, status) in state.statuses(Watched::Config) {
+        let _ = output
+            .send(Message::StatusUpdated(miners[index].0, status))
+            .await;
+    }
+
+    // Decode every change as it arrives
+    let mut updates = stream::select_all(subscriptions);
+    while let Some((kind, account)) = updates.next().await {
+        state.apply(kind, account.decode::<Account>());
+        for (index, status) in state.statuses(kind) {
+            let _ = output
+                .send(Message::StatusUpdated(miners[index].0, status))
+                .await;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{amount_f64_to_u64, amount_u64_to_string};
+    use solana_program::program_pack::Pack;
+    use spl_token::state::AccountState;
+
+    fn token_account(amount: u64) -> Account {
+        let token_account = spl_token::state::Account {
+            amount,
+            state: AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(token_account, &mut data).unwrap();
+        Account {
+            data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn every_miner_shares_one_config_subscription() {
+        let authorities = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let miners = [
+            (authorities[0], Commitment::Processed),
+            (authorities[1], Commitment::Finalized),
+        ];
+        let watched = watched_addresses(&miners);
+        assert_eq!(watched.len(), 9);
+        let configs = watched
+            .iter()
+            .filter(|(_, address, _)| *address == CONFIG_ADDRESS)
+            .count();
+        assert_eq!(configs, 1);
+        assert_eq!(watched[1].1, proof_pubkey(authorities[0]));
+        assert_eq!(
+            watched[6].1,
+            get_associated_token_address(&authorities[1], &MINT_ADDRESS)
+        );
+        assert_eq!(watched[7].1, authorities[1]);
+        assert_eq!(
+            watched[8].1,
+            get_associated_token_address(&authorities[1], &MINT_V1_ADDRESS)
+        );
+    }
+
+    #[test]
+    fn miners_are_watched_at_their_own_commitment() {
+        let miners = [
+            (Pubkey::new_unique(), Commitment::Processed),
+            (Pubkey::new_unique(), Commitment::Finalized),
+        ];
+        let watched = watched_addresses(&miners);
+        assert_eq!(watched[0].2, Commitment::default());
+        assert!(watched[1..5]
+            .iter()
+            .all(|(_, _, c)| *c == Commitment::Processed));
+        assert!(watched[5..]
+            .iter()
+            .all(|(_, _, c)| *c == Commitment::Finalized));
+    }
+
+    #[test]
+    fn token_balance_is_read_without_a_status_until_the_proof_is_known() {
+        let mut state = EndpointState::new(2);
+        state.apply(
+            Watched::Miner(1, WatchedAccount::Token),
+            Some(token_account(amount_f64_to_u64(2.5))),
+        );
+        assert_eq!(
+            state.miners[1].token_balance,
+            Some(amount_u64_to_string(amount_f64_to_u64(2.5)))
+        );
+        assert!(state.miners[0].token_balance.is_none());
+        assert!(state.statuses(Watched::Config).is_empty());
+    }
+}
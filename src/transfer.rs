@@ -0,0 +1,53 @@
+use crate::{
+    cu_limits::CU_LIMIT_TRANSFER, send_and_confirm::ComputeBudget, utils::amount_f64_to_u64, Miner,
+};
+use ore_api::consts::MINT_ADDRESS;
+use solana_program::pubkey::Pubkey;
+use solana_sdk::signature::{Signature, Signer};
+use std::str::FromStr;
+
+pub struct TransferParams {
+    /// The amount of Ore to transfer. Defaults to max.
+    pub amount: Option<f64>,
+    /// Wallet to receive the tokens.
+    pub wallet_address: String,
+}
+
+impl Miner {
+    pub async fn transfer(&self, params: TransferParams) -> Result<Signature, String> {
+        let signer = self.try_signer()?;
+        let to_pubkey = Pubkey::from_str(&params.wallet_address)
+            .map_err(|_| format!("Invalid wallet address: {}", params.wallet_address))?;
+        // Get ATAs
+        let source = spl_associated_token_account::get_associated_token_address(
+            &signer.pubkey(),
+            &MINT_ADDRESS,
+        );
+        let destination = self.initialize_ata(&to_pubkey).await?;
+        // Get token account
+        let Ok(Some(token_account)) = self.rpc_client.get_token_account(&source).await else {
+            return Err("Failed to fetch token account".to_string());
+        };
+        // Parse amount
+        let amount: u64 = if let Some(amount) = params.amount {
+            amount_f64_to_u64(amount)
+        } else {
+            u64::from_str(token_account.token_amount.amount.as_str())
+                .map_err(|_| "Failed to parse token balance".to_string())?
+        };
+
+        // Send tx
+        let ix = spl_token::instruction::transfer(
+            &spl_token::id(),
+            &source,
+            &destination,
+            &signer.pubkey(),
+            &[],
+            amount,
+        )
+        .map_err(|e| format!("Failed to build transfer instruction: {}", e))?;
+        self.send_and_confirm(&[ix], ComputeBudget::Fixed(CU_LIMIT_TRANSFER), false)
+            .await
+            .map_err(|e| format!("Transfer failed: {}", e))
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{chart::ChartRange, utils::format_duration};
+
+/// A change of a miner's online state.
+#[derive(Debug, Clone, Copy)]
+pub struct Transition {
+    pub timestamp: i64,
+    pub online: bool,
+}
+
+/// The availability of a miner over a time range.
+#[derive(Debug, Clone, Default)]
+pub struct Uptime {
+    pub from: i64,
+    pub to: i64,
+    /// Seconds the online state of the miner was known.
+    pub tracked_seconds: i64,
+    pub online_seconds: i64,
+    /// Start and end of every offline period, oldest first.
+    pub outages: Vec<(i64, i64)>,
+}
+
+/// The ranges uptime is reported for.
+pub const UPTIME_RANGES: [ChartRange; 3] = [ChartRange::Day, ChartRange::Week, ChartRange::Month];
+
+impl Uptime {
+    /// Builds the uptime from the transitions within a range, preceded by the last
+    /// transition before it. Time before the first known transition is not tracked.
+    pub fn from_transitions(transitions: &[Transition], from: i64, to: i64) -> Self {
+        let mut uptime = Uptime {
+            from,
+            to,
+            ..Default::default()
+        };
+        for (i, t) in transitions.iter().enumerate() {
+            let start = t.timestamp.clamp(from, to);
+            let end = transitions
+                .get(i + 1)
+                .map_or(to, |next| next.timestamp)
+                .clamp(from, to);
+            if end <= start {
+                continue;
+            }
+            uptime.tracked_seconds += end - start;
+            if t.online {
+                uptime.online_seconds += end - start;
+            } else {
+                uptime.outages.push((start, end));
+            }
+        }
+        uptime
+    }
+
+    /// The share of the tracked time the miner was online, in percent.
+    pub fn percent(&self) -> Option<f64> {
+        if self.tracked_seconds > 0 {
+            Some(self.online_seconds as f64 / self.tracked_seconds as f64 * 100.0)
+        } else {
+            None
+        }
+    }
+
+    /// The length of the longest outage in seconds.
+    pub fn longest_outage(&self) -> i64 {
+        self.outages
+            .iter()
+            .map(|(start, end)| end - start)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The start of the tracked time within the range.
+    /// The tracked time is contiguous, as the last known state holds until the end.
+    pub fn tracked_from(&self) -> i64 {
+        self.to - self.tracked_seconds
+    }
+}
+
+/// Writes a CSV availability report with a row per miner, followed by the fleet total.
+/// Every miner comes with its uptime for each of `UPTIME_RANGES`.
+pub fn availability_report(miners: &[(String, Vec<Uptime>)]) -> String {
+    let mut report = String::from("account");
+    for range in UPTIME_RANGES {
+        report.push_str(&format!(",uptime {} (%)", range));
+    }
+    let longest = UPTIME_RANGES[UPTIME_RANGES.len() - 1];
+    report.push_str(&format!(",longest outage {0},outages {0}\n", longest));
+
+    let mut fleet = vec![Uptime::default(); UPTIME_RANGES.len()];
+    for (account, uptimes) in miners {
+        report.push_str(account);
+        for (total, uptime) in fleet.iter_mut().zip(uptimes) {
+            report.push_str(&format_percent(uptime.percent()));
+            total.tracked_seconds += uptime.tracked_seconds;
+            total.online_seconds += uptime.online_seconds;
+        }
+        let last = uptimes.last().cloned().unwrap_or_default();
+        report.push_str(&format!(
+            ",{},{}\n",
+            format_duration(last.longest_outage()),
+            last.outages.len()
+        ));
+        fleet[UPTIME_RANGES.len() - 1].outages.extend(last.outages);
+    }
+
+    report.push_str("fleet");
+    for total in &fleet {
+        report.push_str(&format_percent(total.percent()));
+    }
+    let last = &fleet[UPTIME_RANGES.len() - 1];
+    report.push_str(&format!(
+        ",{},{}\n",
+        format_duration(last.longest_outage()),
+        last.outages.len()
+    ));
+    report
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!(",{:.2}", percent),
+        None => ",".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transition(timestamp: i64, online: bool) -> Transition {
+        Transition { timestamp, online }
+    }
+
+    #[test]
+    fn time_before_the_first_transition_is_not_tracked() {
+        let transitions = [
+            transition(100, true),
+            transition(300, false),
+            transition(400, true),
+        ];
+        let uptime = Uptime::from_transitions(&transitions, 0, 1000);
+        assert_eq!(uptime.tracked_seconds, 900);
+        assert_eq!(uptime.tracked_from(), 100);
+        assert_eq!(uptime.online_seconds, 800);
+        assert_eq!(uptime.outages, vec![(300, 400)]);
+    }
+
+    #[test]
+    fn state_before_the_range_holds_at_its_start() {
+        let transitions = [transition(-500, false), transition(200, true)];
+        let uptime = Uptime::from_transitions(&transitions, 0, 1000);
+        assert_eq!(uptime.tracked_seconds, 1000);
+        assert_eq!(uptime.percent(), Some(80.0));
+        assert_eq!(uptime.outages, vec![(0, 200)]);
+        assert_eq!(uptime.longest_outage(), 200);
+    }
+
+    #[test]
+    fn untracked_miner_has_no_uptime() {
+        let uptime = Uptime::from_transitions(&[], 0, 1000);
+        assert_eq!(uptime.percent(), None);
+        assert_eq!(uptime.longest_outage(), 0);
+    }
+}
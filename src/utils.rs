@@ -1,40 +1,58 @@
-use crate::consts::SHOW_RPC_URL_MAX_LENGTH;
-use crate::miner::{Config, Configs};
+use crate::config::{back_up, upgrade, write_atomically};
+use crate::consts::{MULTIPLIER_PRECISION, SHOW_RPC_URL_MAX_LENGTH};
+use crate::miner::Configs;
 use cached::proc_macro::cached;
 use chrono::{Local, TimeZone};
 use iced::Theme;
 use ore_api::{
     self,
-    consts::{MINT_ADDRESS, PROOF, TOKEN_DECIMALS, TREASURY_ADDRESS},
+    consts::{MINT_ADDRESS, PROOF, TOKEN_DECIMALS, TOKEN_DECIMALS_V1, TREASURY_ADDRESS},
     state::Proof,
 };
 use ore_utils::AccountDeserialize;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_program::pubkey::Pubkey;
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
 use spl_associated_token_account::get_associated_token_address;
 use std::fs::{self, File};
 use std::io::Read;
-use std::io::Write;
 use std::path::Path;
 use url::Url;
 
-pub async fn get_proof(client: &RpcClient, authority: Pubkey) -> Proof {
+pub async fn get_proof(client: &RpcClient, authority: Pubkey) -> Result<Proof, String> {
     let proof_address = proof_pubkey(authority);
     let data = client
         .get_account_data(&proof_address)
         .await
-        .expect("Failed to get miner account");
-    *Proof::try_from_bytes(&data).expect("Failed to parse miner account")
+        .map_err(|e| format!("Failed to get miner account: {}", e))?;
+    Proof::try_from_bytes(&data)
+        .copied()
+        .map_err(|e| format!("Failed to parse miner account: {}", e))
 }
 
-pub async fn try_get_proof(client: &RpcClient, authority: Pubkey) -> Option<Proof> {
-    let proof_address = proof_pubkey(authority);
-    match client.get_account_data(&proof_address).await {
-        Ok(data) => Some(*Proof::try_from_bytes(&data).expect("Failed to parse miner account")),
-        Err(_) => None,
+/// Calculates the reward multiplier of a proof, which grows linearly with the stake
+/// and caps at 2x once the stake reaches the global top balance.
+pub fn calculate_multiplier(balance: u64, top_balance: u64) -> f64 {
+    if top_balance > 0 {
+        1.0 + (balance as f64 / top_balance as f64).min(1.0)
+    } else {
+        1.0
     }
 }
 
+/// Reads the balance of a token account from its raw data.
+pub fn parse_token_balance(data: &[u8]) -> Option<String> {
+    spl_token::state::Account::unpack(data)
+        .ok()
+        .map(|token_account| amount_u64_to_string(token_account.amount))
+}
+
+/// Reads the balance of a legacy v1 ORE token account, which has fewer decimals.
+pub fn parse_legacy_token_balance(data: &[u8]) -> Option<f64> {
+    spl_token::state::Account::unpack(data)
+        .ok()
+        .map(|token_account| (token_account.amount as f64) / 10f64.powf(TOKEN_DECIMALS_V1 as f64))
+}
+
 pub fn amount_u64_to_string(amount: u64) -> String {
     amount_u64_to_f64(amount).to_string()
 }
@@ -90,34 +108,37 @@ pub fn get_local_time(timestamp: i64) -> String {
     local.format("%Y-%m-%d %H:%M:%S").to_string()
 }
 
-pub fn save_config(configs: &Configs, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+pub fn get_time_of_day(timestamp: i64) -> String {
+    let local = Local.timestamp_opt(timestamp, 0).unwrap();
+    local.format("%H:%M:%S").to_string()
+}
+
+pub fn save_config(configs: &Configs, file_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(dir) = file_path.parent() {
+        fs::create_dir_all(dir)?;
+    }
     let toml_string = toml::to_string(configs)?;
-    let mut file = File::create(file_path)?;
-    file.write_all(toml_string.as_bytes())?;
+    back_up(file_path, &toml_string)?;
+    write_atomically(file_path, toml_string.as_bytes())?;
     Ok(())
 }
 
-pub fn load_config(file_path: &str) -> Result<Configs, Box<dyn std::error::Error>> {
+pub fn load_config(file_path: &Path) -> Result<Configs, Box<dyn std::error::Error>> {
     let mut file = match File::open(file_path) {
         Ok(file) => file,
         Err(_) => return Ok(Configs::default()),
     };
     let mut content = String::new();
     file.read_to_string(&mut content)?;
-    let configs: Configs = toml::from_str(&content)?;
+    // Upgrade files written by earlier versions before reading them
+    let mut table: toml::Table = toml::from_str(&content)?;
+    if upgrade(&mut table) {
+        println!("Upgraded {}", file_path.display());
+    }
+    let configs: Configs = toml::Value::Table(table).try_into()?;
     Ok(configs)
 }
 
-pub fn append_config(config: Config, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let mut configs = load_config(file_path)?;
-    configs.configs.push(config);
-
-    let toml_string = toml::to_string(&configs)?;
-    let mut file = File::create(file_path)?;
-    file.write_all(toml_string.as_bytes())?;
-    Ok(())
-}
-
 pub fn is_valid_path(file_path: &str) -> bool {
     let path = Path::new(file_path);
     if path.exists() {
@@ -160,3 +181,66 @@ pub fn round_dp(decimal: f64, precision: u8) -> f64 {
     let factor = 10f64.powi(precision.into());
     (decimal * factor).round() / factor
 }
+
+/// Formats a number of seconds as days, hours and minutes, e.g. `1d 2h 5m`.
+pub fn format_duration(seconds: i64) -> String {
+    let minutes = seconds / 60;
+    let (days, hours, minutes) = (minutes / 1440, minutes / 60 % 24, minutes % 60);
+    if days > 0 {
+        format!("{}d {}h {}m", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn format_multiplier(multiplier: f64) -> String {
+    format!("{}x", round_dp(multiplier, MULTIPLIER_PRECISION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multiplier_grows_with_the_stake_and_caps_at_two() {
+        assert_eq!(calculate_multiplier(500, 0), 1.0);
+        assert_eq!(calculate_multiplier(0, 1000), 1.0);
+        assert_eq!(calculate_multiplier(500, 1000), 1.5);
+        assert_eq!(calculate_multiplier(1000, 1000), 2.0);
+        assert_eq!(calculate_multiplier(5000, 1000), 2.0);
+    }
+
+    #[test]
+    fn multiplier_is_rounded_for_display() {
+        assert_eq!(format_multiplier(1.0), "1x");
+        assert_eq!(format_multiplier(1.123456), "1.1235x");
+    }
+
+    #[test]
+    fn token_balance_is_read_from_raw_account_data() {
+        let token_account = spl_token::state::Account {
+            amount: amount_f64_to_u64(2.5),
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(token_account, &mut data).unwrap();
+        assert_eq!(parse_token_balance(&data), Some("2.5".to_string()));
+        assert_eq!(parse_token_balance(&[]), None);
+    }
+
+    #[test]
+    fn legacy_token_balance_uses_the_v1_decimals() {
+        let token_account = spl_token::state::Account {
+            amount: 3 * 10u64.pow(TOKEN_DECIMALS_V1 as u32),
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(token_account, &mut data).unwrap();
+        assert_eq!(parse_legacy_token_balance(&data), Some(3.0));
+        assert_eq!(parse_legacy_token_balance(&[]), None);
+    }
+}
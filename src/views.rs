@@ -1,18 +1,33 @@
 use crate::{
+    alerts::AlertKind,
+    automation::AutomationRule,
+    balance::FetchError,
+    chart::{hashes_per_hour, Chart, ChartRange, Timeline, PRICE_RANGES},
     circular::Circular,
+    cluster::{Cluster, Commitment},
+    config,
     consts::{
-        ACCOUNT_DETAIL_HIGHT, ACCOUNT_DETAIL_WIDTH, MAX_ITEMS_PER_ROW, MENU_CATEGORY_SPACING,
-        MENU_ITEM_INDENT, MENU_ITEM_SPACING, MENU_SPAN_HEIGHT, SUBHEAD_TEXT,
+        ACCOUNT_DETAIL_HIGHT, ACCOUNT_DETAIL_WIDTH, AUTOMATION_LOG_SIZE, BALANCE_PRECISION,
+        CHART_HEIGHT, MAX_ITEMS_PER_ROW, MENU_CATEGORY_SPACING, MENU_ITEM_INDENT,
+        MENU_ITEM_SPACING, MENU_SPAN_HEIGHT, PRICE_CHANGE_SECONDS, SCHEDULE_UPCOMING_RUNS,
+        SUBHEAD_TEXT, TIMELINE_HEIGHT,
     },
+    earnings::Earnings,
     easing,
-    logic::FetchMode,
+    logic::{CommitmentKind, FetchMode, SettingField},
+    portfolio::{Asset, Holdings, Prices},
+    price::Currency,
     style,
-    utils::{abbreviate, get_domain},
+    uptime::UPTIME_RANGES,
+    utils::{
+        abbreviate, format_duration, format_multiplier, get_domain, get_local_time,
+        get_time_of_day, round_dp,
+    },
     Account, ContentType, Dashboard, Message, ModalType,
 };
 use iced::widget::{
-    button, center, checkbox, column, container, horizontal_space, mouse_area, opaque, pick_list,
-    row, scrollable, stack, svg, text, text_input, vertical_space, Column, Row,
+    button, canvas, center, checkbox, column, container, horizontal_space, mouse_area, opaque,
+    pick_list, row, scrollable, stack, svg, text, text_input, vertical_space, Column, Row,
 };
 use iced::{padding, Color, Element, Length, Theme};
 use ore_api::consts::MINT_ADDRESS;
@@ -35,6 +50,9 @@ impl Dashboard {
                     text("Number:"),
                     text("Balance:").height(MENU_SPAN_HEIGHT),
                     text("Stake:").height(MENU_SPAN_HEIGHT),
+                    text("Earnings:").height(MENU_SPAN_HEIGHT),
+                    text("Per hash:"),
+                    text("Price:").height(MENU_SPAN_HEIGHT),
                     text("Status:"),
                     text("Mint Address:")
                 ]
@@ -44,14 +62,20 @@ impl Dashboard {
                     text(self.accounts.len()),
                     column![
                         text(&self.balance),
-                        text(format!("${}", &self.balance_usd)).size(SUBHEAD_TEXT)
+                        text(&self.balance_value).size(SUBHEAD_TEXT)
                     ]
                     .align_x(iced::Alignment::End),
                     column![
                         text(&self.stake),
-                        text(format!("${}", &self.stake_usd)).size(SUBHEAD_TEXT)
+                        text(&self.stake_value).size(SUBHEAD_TEXT)
                     ]
                     .align_x(iced::Alignment::End),
+                    fleet_earnings_view(self),
+                    text(match self.earnings {
+                        Some(e) => format!("{:.3e}", e.per_hash()),
+                        None => "--".to_string(),
+                    }),
+                    price_view(self),
                     active_num_view(&self),
                     text(abbreviate(&MINT_ADDRESS.to_string()))
                 ]
@@ -59,15 +83,30 @@ impl Dashboard {
                 .spacing(MENU_ITEM_SPACING)
             ],
             column![
+                profile_view(self),
                 checkbox("Auto Refresh", self.auto_refresh).on_toggle(Message::ToggleSubscription),
-                checkbox(
-                    "Parallel Mode",
-                    match self.fetch_mode {
-                        FetchMode::Parallel => true,
-                        FetchMode::Serial => false,
-                    }
-                )
-                .on_toggle(Message::ToggleFetchMode),
+                checkbox("Pause Automation", self.automation_paused)
+                    .on_toggle(Message::PauseAutomation),
+                row![
+                    text("Fetch Mode:"),
+                    pick_list(
+                        FetchMode::ALL,
+                        Some(self.fetch_mode),
+                        Message::FetchModeSelected
+                    )
+                ]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
+                row![
+                    text("Currency:"),
+                    pick_list(
+                        currency_options(&self.currency),
+                        Some(self.currency.clone()),
+                        Message::CurrencySelected
+                    )
+                ]
+                .spacing(5)
+                .align_y(iced::Alignment::Center),
             ]
             .spacing(MENU_ITEM_SPACING),
             row![
@@ -75,6 +114,50 @@ impl Dashboard {
                     .on_press(Message::SetModalView(None, add_account_view))
                     .width(Length::Fill)
             ],
+            row![button(text("RPC health").align_x(iced::Alignment::Center))
+                .on_press(Message::SetModalView(None, health_view))
+                .width(Length::Fill)
+                .style(button::secondary)],
+            row![button(
+                text(format!("Alerts ({})", self.alerts.len())).align_x(iced::Alignment::Center)
+            )
+            .on_press(Message::SetModalView(None, alerts_view))
+            .width(Length::Fill)
+            .style(button::secondary)],
+            row![button(text("Price chart").align_x(iced::Alignment::Center))
+                .on_press(Message::ShowPriceChart)
+                .width(Length::Fill)
+                .style(button::secondary)],
+            row![button(text("Portfolio").align_x(iced::Alignment::Center))
+                .on_press(Message::SetModalView(None, portfolio_view))
+                .width(Length::Fill)
+                .style(button::secondary)],
+            row![
+                button(text("Automation log").align_x(iced::Alignment::Center))
+                    .on_press(Message::SetModalView(None, automation_view))
+                    .width(Length::Fill)
+                    .style(button::secondary)
+            ],
+            row![button(text("Schedules").align_x(iced::Alignment::Center))
+                .on_press(Message::SetModalView(None, schedule_view))
+                .width(Length::Fill)
+                .style(button::secondary)],
+            row![
+                button(text("Availability report").align_x(iced::Alignment::Center))
+                    .on_press(Message::ExportAvailability)
+                    .width(Length::Fill)
+                    .style(button::secondary)
+            ],
+            row![
+                button(text("Stake projection").align_x(iced::Alignment::Center))
+                    .on_press(Message::SetModalView(None, stake_projection_view))
+                    .width(Length::Fill)
+                    .style(button::secondary)
+            ],
+            row![button(text("Settings").align_x(iced::Alignment::Center))
+                .on_press(Message::ShowSettings)
+                .width(Length::Fill)
+                .style(button::secondary)],
             vertical_space(),
             // Themes
             row!(pick_list(
@@ -147,7 +230,7 @@ pub fn get_content_list<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
     let mut count = 0;
 
     // Load accounts
-    for (i, a) in dashboard.accounts.iter().enumerate() {
+    for a in &dashboard.accounts {
         if count >= MAX_ITEMS_PER_ROW + dashboard.extend_items_per_row {
             columns = columns.push(rows);
             rows = row![].spacing(5);
@@ -155,7 +238,7 @@ pub fn get_content_list<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
         }
 
         rows = rows.push(
-            container(get_content2(i, &a))
+            container(get_content2(dashboard, a))
                 .width(ACCOUNT_DETAIL_WIDTH)
                 .height(ACCOUNT_DETAIL_HIGHT)
                 .style(container::rounded_box),
@@ -171,7 +254,10 @@ pub fn get_content_list<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
 }
 
 /// Displays detailed content for an account.
-pub fn get_content2<'a>(index: usize, account: &'a Account) -> Element<'a, Message> {
+pub fn get_content2<'a>(dashboard: &'a Dashboard, account: &'a Account) -> Element<'a, Message> {
+    let id = account.id;
+    let endpoint_healthy = dashboard.is_endpoint_healthy(&account.json_rpc_url);
+    let cluster = dashboard.cluster_of(account);
     let status = &account.status;
     let prepared = account.prepared;
     if !prepared {
@@ -194,8 +280,26 @@ pub fn get_content2<'a>(index: usize, account: &'a Account) -> Element<'a, Messa
                 get_svg(status.is_online),
             ]
             .spacing(5),
-            row![text("Balance:"), text(&status.balance)].spacing(5),
-            row![text("Stake:"), text(&status.stake)].spacing(5),
+            row![
+                text("Balance:"),
+                text(&status.balance),
+                horizontal_space(),
+                text(format!(
+                    "{} SOL",
+                    round_dp(status.sol_balance, BALANCE_PRECISION)
+                ))
+                .size(SUBHEAD_TEXT)
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
+            row![
+                text("Stake:"),
+                text(&status.stake),
+                horizontal_space(),
+                text(format_multiplier(status.multiplier)).size(SUBHEAD_TEXT)
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
             row![
                 text("Last hash time: ").size(SUBHEAD_TEXT),
                 text(&status.last_hash_at).size(SUBHEAD_TEXT)
@@ -217,22 +321,40 @@ pub fn get_content2<'a>(index: usize, account: &'a Account) -> Element<'a, Messa
             ]
             .spacing(5),
             row![
-                text("Rpc:").size(SUBHEAD_TEXT),
-                text(get_domain(&account.json_rpc_url)).size(SUBHEAD_TEXT)
+                text("Earnings:").size(SUBHEAD_TEXT),
+                text(format_earnings(dashboard, account.earnings)).size(SUBHEAD_TEXT)
             ]
             .spacing(5),
-            vertical_space(),
+            rpc_row(account, endpoint_healthy, cluster),
+        ]
+        .push_maybe(account.is_stale().then(|| {
+            text(format!(
+                "Stale: {}",
+                account
+                    .error
+                    .as_ref()
+                    .map(|e| e.to_string())
+                    .unwrap_or_default()
+            ))
+            .size(SUBHEAD_TEXT)
+            .style(text::danger)
+        }))
+        .push(vertical_space())
+        .push(
             column![row![
                 button("Claim")
-                    .on_press(Message::SetModalView(Some(index), claim_view))
+                    .on_press(Message::SetModalView(Some(id), claim_view))
                     .style(button::success),
-                button("Stake").on_press(Message::SetModalView(Some(index), stake_view)),
-                button("Remove").on_press(Message::SetModalView(Some(index), remove_account_view)),
+                button("Stake").on_press(Message::SetModalView(Some(id), stake_view)),
+                button("Details")
+                    .on_press(Message::ShowDetail(id))
+                    .style(button::secondary),
+                button("Remove").on_press(Message::SetModalView(Some(id), remove_account_view)),
             ]
             .spacing(5)]
             .width(Length::Fill)
             .align_x(iced::Alignment::Center),
-        ]
+        )
         .padding(5)
         .spacing(8)
         .height(Length::Fill)
@@ -248,14 +370,13 @@ pub fn get_content2<'a>(index: usize, account: &'a Account) -> Element<'a, Messa
                 get_svg(status.is_online),
             ]
             .spacing(5),
-            row![
-                text("Rpc:").size(SUBHEAD_TEXT),
-                text(get_domain(&account.json_rpc_url)).size(SUBHEAD_TEXT)
-            ]
-            .spacing(5),
-            text("Miner account doesn't exist"),
+            rpc_row(account, endpoint_healthy, cluster),
+            match &account.error {
+                Some(FetchError::MissingProof(_)) | None => text("Miner account doesn't exist"),
+                Some(e) => text(e.to_string()).style(text::danger),
+            },
             vertical_space(),
-            column![button("Remove").on_press(Message::RemoveAccount(index))]
+            column![button("Remove").on_press(Message::RemoveAccount(id))]
                 .width(Length::Fill)
                 .align_x(iced::Alignment::Center)
         ]
@@ -268,12 +389,112 @@ pub fn get_content2<'a>(index: usize, account: &'a Account) -> Element<'a, Messa
     }
 }
 
+/// Describes the earnings rate per hour and per day, priced in the display currency.
+fn format_earnings(dashboard: &Dashboard, earnings: Option<Earnings>) -> String {
+    match earnings {
+        Some(e) => format!(
+            "{}/h, {}/d ({}/d)",
+            round_dp(e.per_hour, BALANCE_PRECISION),
+            round_dp(e.per_day(), BALANCE_PRECISION),
+            dashboard.format_value(e.per_day())
+        ),
+        None => "Not enough history yet".to_string(),
+    }
+}
+
+/// Picks the profile in use, warning when its accounts point at different clusters.
+fn profile_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let mixed = dashboard.mixed_clusters();
+    column![row![
+        text("Profile:"),
+        pick_list(
+            dashboard
+                .profiles
+                .iter()
+                .map(|p| p.name.clone())
+                .collect::<Vec<_>>(),
+            Some(dashboard.profile.clone()),
+            Message::ProfileSelected
+        ),
+        button(text("New"))
+            .on_press(Message::SetModalView(None, add_profile_view))
+            .style(button::secondary),
+    ]
+    .spacing(5)
+    .align_y(iced::Alignment::Center)]
+    .push_maybe((!mixed.is_empty()).then(|| {
+        let names: Vec<String> = mixed.iter().map(Cluster::to_string).collect();
+        text(format!("Accounts point at {}", names.join(", ")))
+            .size(SUBHEAD_TEXT)
+            .style(text::danger)
+    }))
+    .spacing(5)
+    .into()
+}
+
+/// Lets a cluster preset fill in an RPC URL. The preset of a URL is selected as it's typed.
+fn cluster_picker<'a>(url: &str, on_select: impl Fn(String) -> Message + 'a) -> Row<'a, Message> {
+    let current = url.to_string();
+    row![
+        text("Cluster:").size(SUBHEAD_TEXT),
+        pick_list(
+            Cluster::ALL,
+            Some(Cluster::from_url(url)),
+            move |cluster: Cluster| on_select(cluster.select_url(&current))
+        ),
+    ]
+    .spacing(5)
+    .align_y(iced::Alignment::Center)
+}
+
+/// Lets the commitment of reads and of confirmations be picked, for an account or for the
+/// one being added.
+fn commitment_row<'a>(id: Option<u64>, read: Commitment, confirm: Commitment) -> Row<'a, Message> {
+    row![
+        text("Reads:").size(SUBHEAD_TEXT),
+        pick_list(Commitment::ALL, Some(read), move |c| {
+            Message::CommitmentSelected(id, CommitmentKind::Read, c)
+        }),
+        text("Confirmations:").size(SUBHEAD_TEXT),
+        pick_list(Commitment::ALL, Some(confirm), move |c| {
+            Message::CommitmentSelected(id, CommitmentKind::Confirm, c)
+        }),
+    ]
+    .spacing(5)
+    .align_y(iced::Alignment::Center)
+}
+
+/// Shows the endpoint in use by an account and its cluster, flagged when it is a fallback
+/// or unhealthy.
+fn rpc_row<'a>(account: &'a Account, endpoint_healthy: bool, cluster: Cluster) -> Row<'a, Message> {
+    row![
+        text("Rpc:").size(SUBHEAD_TEXT),
+        text(get_domain(&account.json_rpc_url)).size(SUBHEAD_TEXT),
+        container(text(cluster.to_string()).size(SUBHEAD_TEXT))
+            .padding([0, 4])
+            .style(style::badge),
+    ]
+    .push_maybe((account.rpc_index > 0).then(|| {
+        text(format!("(fallback {})", account.rpc_index))
+            .size(SUBHEAD_TEXT)
+            .style(text::secondary)
+    }))
+    .push_maybe(
+        (!endpoint_healthy).then(|| text("(unhealthy)").size(SUBHEAD_TEXT).style(text::danger)),
+    )
+    .spacing(5)
+}
+
 pub fn add_account_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
     container(
         column![
             text("Add an account").size(24),
             text("Json rpc url"),
+            cluster_picker(&dashboard.json_rpc_url, Message::JsonRpcUrl),
             text_input("", &dashboard.json_rpc_url).on_input(Message::JsonRpcUrl),
+            text("Fallback rpc urls").size(12),
+            text_input("(optional, comma separated)", &dashboard.fallback_rpc_urls)
+                .on_input(Message::FallbackRpcUrls),
             text("Key pair").size(12),
             row![
                 text_input("File path", &dashboard.keypair)
@@ -284,6 +505,12 @@ pub fn add_account_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
             .spacing(10),
             text("Priority fee"),
             text_input("", &dashboard.priority_fee).on_input(Message::PriorityFee),
+            text("Commitment"),
+            commitment_row(
+                None,
+                dashboard.read_commitment,
+                dashboard.confirm_commitment
+            ),
             container(button(text("Add")).on_press(Message::AddAccount))
                 .align_x(iced::Alignment::Center),
         ]
@@ -295,9 +522,41 @@ pub fn add_account_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
     .into()
 }
 
+pub fn add_profile_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
+    container(
+        column![
+            text("Add a profile").size(24),
+            text(format!(
+                "{} is saved before switching to the new profile",
+                dashboard.profile
+            ))
+            .size(SUBHEAD_TEXT),
+            text("Name"),
+            text_input("e.g. mainnet", &dashboard.new_profile).on_input(Message::NewProfileName),
+            text("Default json rpc url"),
+            cluster_picker(&dashboard.new_profile_rpc_url, Message::NewProfileRpcUrl),
+            text_input("", &dashboard.new_profile_rpc_url)
+                .on_input(Message::NewProfileRpcUrl)
+                .on_submit(Message::AddProfile),
+            row![
+                button(text("Add")).on_press(Message::AddProfile),
+                button(text("Cancel"))
+                    .on_press(Message::HideModal(None))
+                    .style(button::secondary),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(400)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
 pub fn remove_account_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
-    if let Some(index) = dashboard.current_index {
-        let account = dashboard.accounts.get(index).expect("No account selected");
+    if let Some(id) = dashboard.current_id {
+        let account = dashboard.account(id).expect("No account selected");
         let pubkey = account.status.authority.to_string();
         container(
             Column::new()
@@ -308,7 +567,7 @@ pub fn remove_account_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message>
                 .push(
                     Row::new()
                         .spacing(20)
-                        .push(button(text("Yes")).on_press(Message::RemoveAccount(index)))
+                        .push(button(text("Yes")).on_press(Message::RemoveAccount(id)))
                         .push(button(text("Cancel")).on_press(Message::HideModal(None))),
                 ),
         )
@@ -400,6 +659,7 @@ pub fn stake_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
                         .on_input(Message::StakeAmount)
                         .on_submit(Message::SetModalView(None, stake_confirm_view))
                         .padding(5),
+                    text(stake_projection_text(dashboard)).size(SUBHEAD_TEXT),
                 ]
                 .spacing(5),
                 row![
@@ -418,6 +678,24 @@ pub fn stake_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
     .into()
 }
 
+/// Describes how the multiplier of the selected account changes with the stake amount.
+fn stake_projection_text(dashboard: &Dashboard) -> String {
+    let Some(account) = dashboard.current_id.and_then(|id| dashboard.account(id)) else {
+        return String::default();
+    };
+    let status = &account.status;
+    // Staking without an amount moves the whole wallet balance
+    let extra = dashboard
+        .stake_amount
+        .parse::<f64>()
+        .unwrap_or(status.balance.parse::<f64>().unwrap_or(0.0));
+    format!(
+        "Multiplier: {} -> {}",
+        format_multiplier(status.multiplier),
+        format_multiplier(status.projected_multiplier(extra))
+    )
+}
+
 pub fn stake_confirm_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
     container(
         Column::new()
@@ -451,6 +729,732 @@ pub fn stake_confirm_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message>
     .into()
 }
 
+pub fn stake_projection_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let extra = dashboard.projection_amount.parse::<f64>().unwrap_or(0.0);
+    // Find the account that gains the most from the extra stake
+    let gains: Vec<Option<f64>> = dashboard
+        .accounts
+        .iter()
+        .map(|a| {
+            if a.prepared && a.status.is_valid {
+                Some(a.status.projected_multiplier(extra) - a.status.multiplier)
+            } else {
+                None
+            }
+        })
+        .collect();
+    let best = gains
+        .iter()
+        .enumerate()
+        .filter_map(|(i, gain)| gain.map(|g| (i, g)))
+        .filter(|(_, g)| *g > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i);
+
+    let mut rows = column![row![
+        text("Account").width(Length::FillPortion(2)),
+        text("Current").width(Length::FillPortion(1)),
+        text("Projected").width(Length::FillPortion(1)),
+    ]
+    .spacing(5)]
+    .spacing(5);
+    for (i, (a, gain)) in dashboard.accounts.iter().zip(gains).enumerate() {
+        let Some(_) = gain else {
+            continue;
+        };
+        let projected = text(format_multiplier(a.status.projected_multiplier(extra)))
+            .width(Length::FillPortion(1));
+        rows = rows.push(
+            row![
+                text(abbreviate(&a.status.authority.to_string())).width(Length::FillPortion(2)),
+                text(format_multiplier(a.status.multiplier)).width(Length::FillPortion(1)),
+                if Some(i) == best {
+                    projected.style(text::success)
+                } else {
+                    projected
+                },
+            ]
+            .spacing(5),
+        );
+    }
+
+    container(
+        column![
+            text("Stake projection").size(24),
+            text("Extra stake").size(12),
+            text_input("0", &dashboard.projection_amount)
+                .on_input(Message::ProjectionAmount)
+                .padding(5),
+            scrollable(rows).height(Length::Shrink),
+            text("The account with the highest multiplier gain is highlighted.").size(SUBHEAD_TEXT),
+            button(text("Close")).on_press(Message::HideModal(None)),
+        ]
+        .spacing(10),
+    )
+    .width(450)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+pub fn health_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let mut urls: Vec<&str> = dashboard
+        .rpc_pool
+        .endpoints()
+        .map(|endpoint| endpoint.url.as_str())
+        .collect();
+    urls.sort();
+
+    let mut rows = column![row![
+        text("Endpoint").width(Length::FillPortion(3)),
+        text("Latency").width(Length::FillPortion(1)),
+        text("Slot lag").width(Length::FillPortion(1)),
+        text("Errors").width(Length::FillPortion(1)),
+        text("Accounts").width(Length::FillPortion(1)),
+        horizontal_space().width(3),
+    ]
+    .spacing(5)]
+    .spacing(5);
+    for url in &urls {
+        let health = dashboard.health.get(*url);
+        let latency = health
+            .and_then(|h| h.latency)
+            .map(|latency| format!("{} ms", latency.as_millis()))
+            .unwrap_or("-".to_string());
+        let slot_lag = health
+            .filter(|h| h.slot.is_some())
+            .map(|h| h.slot_lag.to_string())
+            .unwrap_or("-".to_string());
+        let error_rate = health
+            .map(|h| format!("{:.0}%", h.error_rate() * 100.0))
+            .unwrap_or("-".to_string());
+        let accounts = dashboard
+            .accounts
+            .iter()
+            .filter(|a| a.json_rpc_url == *url)
+            .count();
+        rows = rows.push(
+            row![
+                text(get_domain(url)).width(Length::FillPortion(3)),
+                text(latency).width(Length::FillPortion(1)),
+                text(slot_lag).width(Length::FillPortion(1)),
+                text(error_rate).width(Length::FillPortion(1)),
+                text(accounts).width(Length::FillPortion(1)),
+                get_svg(dashboard.is_endpoint_healthy(url)),
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    // Only endpoints that passed a probe are offered as a target
+    let targets: Vec<String> = urls
+        .iter()
+        .filter(|url| dashboard.health.contains_key(**url) && dashboard.is_endpoint_healthy(url))
+        .map(|url| url.to_string())
+        .collect();
+    let unhealthy = dashboard
+        .accounts
+        .iter()
+        .filter(|a| !dashboard.is_endpoint_healthy(&a.json_rpc_url))
+        .count();
+
+    container(
+        column![
+            text("RPC health").size(24),
+            scrollable(rows).height(Length::Shrink),
+            text(format!("{} account(s) on unhealthy endpoints", unhealthy)).size(SUBHEAD_TEXT),
+            row![
+                text("Move to:"),
+                pick_list(
+                    targets,
+                    dashboard.move_target.clone(),
+                    Message::MoveTargetSelected
+                ),
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
+            row![
+                button(text("Move")).on_press_maybe(
+                    (unhealthy > 0 && dashboard.move_target.is_some())
+                        .then_some(Message::MoveUnhealthyAccounts)
+                ),
+                button(text("Close")).on_press(Message::HideModal(None)),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+pub fn alerts_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let rules = dashboard
+        .alerts_config
+        .rules
+        .iter()
+        .map(|rule| match rule.kind {
+            AlertKind::LowSol | AlertKind::StakeAbove => {
+                format!("{} ({})", rule.kind, rule.threshold)
+            }
+            _ => rule.kind.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let list = if dashboard.alerts.is_empty() {
+        column![text("No alerts yet")]
+    } else {
+        Column::with_children(
+            dashboard
+                .alerts
+                .iter()
+                .map(|alert| text(alert.to_string()).size(SUBHEAD_TEXT).into()),
+        )
+        .spacing(5)
+    };
+
+    container(
+        column![
+            text("Alerts").size(24),
+            text(format!("Rules: {}", rules)).size(SUBHEAD_TEXT),
+            scrollable(list).height(Length::Shrink),
+            row![
+                button(text("Clear"))
+                    .on_press_maybe((!dashboard.alerts.is_empty()).then_some(Message::ClearAlerts))
+                    .style(button::secondary),
+                button(text("Close")).on_press(Message::HideModal(None)),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+pub fn automation_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let mut rules = column![text("Rules")].spacing(5);
+    for (account, config) in dashboard.accounts.iter().zip(&dashboard.configs) {
+        for rule in &config.automation {
+            rules = rules.push(
+                text(format!(
+                    "{}: {}",
+                    abbreviate(&account.status.authority.to_string()),
+                    format_rule(rule)
+                ))
+                .size(SUBHEAD_TEXT),
+            );
+        }
+    }
+    if dashboard.configs.iter().all(|c| c.automation.is_empty()) {
+        rules = rules.push(
+            text(format!("No rules in {}", dashboard.config_path.display())).size(SUBHEAD_TEXT),
+        );
+    }
+
+    let mut runs = column![text("Recent runs")].spacing(5);
+    for run in dashboard
+        .automation_runs
+        .iter()
+        .rev()
+        .take(AUTOMATION_LOG_SIZE)
+    {
+        let outcome = match (&run.error, run.succeeded) {
+            (Some(e), _) => format!("failed: {}", e),
+            (None, true) => "succeeded".to_string(),
+            (None, false) => "failed".to_string(),
+        };
+        runs = runs.push(
+            text(format!(
+                "{} {} {} {} {}",
+                get_local_time(run.timestamp),
+                abbreviate(&run.authority.to_string()),
+                run.action,
+                format_amount_at(dashboard, run.amount, run.timestamp),
+                outcome
+            ))
+            .size(SUBHEAD_TEXT),
+        );
+    }
+    if dashboard.automation_runs.is_empty() {
+        runs = runs.push(text("No runs yet").size(SUBHEAD_TEXT));
+    }
+
+    container(
+        column![
+            text("Automation").size(24),
+            scrollable(column![rules, runs].spacing(10)).height(Length::Shrink),
+            button(text("Close")).on_press(Message::HideModal(None)),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+pub fn schedule_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let now = chrono::Utc::now().timestamp();
+    let mut jobs = column![text("Jobs")].spacing(5);
+    for job in &dashboard.jobs {
+        let config = &job.config;
+        let targets = if config.accounts.is_empty() {
+            "every account".to_string()
+        } else {
+            format!("{} account(s)", config.accounts.len())
+        };
+        let mut line = format!(
+            "{}: {} from {} at \"{}\" ({})",
+            config.name, config.action, targets, config.cron, config.timezone
+        );
+        if let Some(wallet) = &config.wallet_address {
+            line.push_str(&format!(" to {}", abbreviate(wallet)));
+        }
+        jobs = jobs.push(text(line).size(SUBHEAD_TEXT));
+        if let Some(e) = job.error() {
+            jobs = jobs.push(text(e).size(SUBHEAD_TEXT).style(text::danger));
+        }
+    }
+    if dashboard.jobs.is_empty() {
+        jobs = jobs.push(
+            text(format!("No jobs in {}", dashboard.config_path.display())).size(SUBHEAD_TEXT),
+        );
+    }
+
+    // Merge the next runs of every job
+    let mut upcoming: Vec<_> = dashboard
+        .jobs
+        .iter()
+        .flat_map(|job| {
+            job.upcoming(now, SCHEDULE_UPCOMING_RUNS)
+                .into_iter()
+                .map(move |time| (time, job))
+        })
+        .collect();
+    upcoming.sort_by_key(|(time, _)| time.timestamp());
+    let mut next_runs = column![text("Upcoming runs")].spacing(5);
+    for (time, job) in upcoming.iter().take(SCHEDULE_UPCOMING_RUNS) {
+        next_runs = next_runs.push(
+            text(format!(
+                "{} {} ({})",
+                time.format("%Y-%m-%d %H:%M %Z"),
+                job.config.name,
+                job.config.action
+            ))
+            .size(SUBHEAD_TEXT),
+        );
+    }
+
+    let mut past_runs = column![text("Past runs")].spacing(5);
+    for run in &dashboard.schedule_runs {
+        let amount = match run.amount {
+            Some(amount) => format_amount_at(dashboard, amount, run.timestamp),
+            None => "all ORE".to_string(),
+        };
+        past_runs = past_runs.push(
+            text(format!(
+                "{} {} {} {} {} {}",
+                get_local_time(run.timestamp),
+                run.job,
+                abbreviate(&run.authority.to_string()),
+                run.action,
+                amount,
+                match (&run.error, run.succeeded) {
+                    (Some(e), _) => format!("failed: {}", e),
+                    (None, true) => "succeeded".to_string(),
+                    (None, false) => "failed".to_string(),
+                }
+            ))
+            .size(SUBHEAD_TEXT),
+        );
+    }
+    if dashboard.schedule_runs.is_empty() {
+        past_runs = past_runs.push(text("No runs yet").size(SUBHEAD_TEXT));
+    }
+
+    container(
+        column![
+            text("Schedules").size(24),
+            scrollable(column![jobs, next_runs, past_runs].spacing(10)).height(Length::Shrink),
+            button(text("Close")).on_press(Message::HideModal(None)),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+pub fn portfolio_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let prices = dashboard.prices();
+    let now = chrono::Utc::now().timestamp();
+    let prices_before = dashboard
+        .price_at(now - PRICE_CHANGE_SECONDS)
+        .map(Prices::from_point);
+    let accounts: Vec<(&Account, Holdings)> = dashboard
+        .accounts
+        .iter()
+        .filter(|a| a.status.is_valid)
+        .map(|a| (a, Holdings::from_status(&a.status)))
+        .collect();
+    let mut fleet = Holdings::default();
+    for (_, holdings) in &accounts {
+        fleet += *holdings;
+    }
+
+    let mut sections = column![
+        text("Fleet"),
+        holdings_view(dashboard, &fleet, prices.as_ref()),
+        text(format!(
+            "24h change: {}",
+            format_change(dashboard, &fleet, prices.as_ref(), prices_before.as_ref())
+        ))
+        .size(SUBHEAD_TEXT),
+    ]
+    .spacing(5);
+    for (account, holdings) in &accounts {
+        sections = sections.push(
+            column![
+                text(abbreviate(&account.status.authority.to_string())),
+                holdings_view(dashboard, holdings, prices.as_ref()),
+                text(format!(
+                    "24h change: {}",
+                    format_change(dashboard, holdings, prices.as_ref(), prices_before.as_ref())
+                ))
+                .size(SUBHEAD_TEXT),
+            ]
+            .spacing(5)
+            .padding(padding::top(10)),
+        );
+    }
+
+    container(
+        column![
+            text("Portfolio").size(24),
+            scrollable(sections).height(Length::Shrink),
+            button(text("Close")).on_press(Message::HideModal(None)),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+/// Lists the amount, value and share of every asset, followed by the total value.
+fn holdings_view<'a>(
+    dashboard: &Dashboard,
+    holdings: &Holdings,
+    prices: Option<&Prices>,
+) -> Element<'a, Message> {
+    let format_value = |value: Option<f64>| match value {
+        Some(value) => dashboard.currency.format(value),
+        None => "--".to_string(),
+    };
+    let mut rows = column![row![
+        text("Asset").width(Length::FillPortion(2)),
+        text("Amount").width(Length::FillPortion(2)),
+        text("Value").width(Length::FillPortion(2)),
+        text("Share").width(Length::FillPortion(1)),
+    ]
+    .spacing(5)]
+    .spacing(5);
+    for asset in Asset::ALL {
+        let unit = if Asset::Sol == asset { "SOL" } else { "ORE" };
+        let share = prices
+            .and_then(|p| holdings.share(asset, p))
+            .map(|share| format!("{:.1}%", share * 100.0))
+            .unwrap_or("--".to_string());
+        rows = rows.push(
+            row![
+                text(asset.to_string()).width(Length::FillPortion(2)),
+                text(format!(
+                    "{} {}",
+                    round_dp(holdings.amount(asset), BALANCE_PRECISION),
+                    unit
+                ))
+                .width(Length::FillPortion(2)),
+                text(format_value(
+                    prices.and_then(|p| holdings.value_of(asset, p))
+                ))
+                .width(Length::FillPortion(2)),
+                text(share).width(Length::FillPortion(1)),
+            ]
+            .spacing(5),
+        );
+    }
+    rows.push(
+        row![
+            text("Total").width(Length::FillPortion(4)),
+            text(format_value(prices.map(|p| holdings.value(p)))).width(Length::FillPortion(3)),
+        ]
+        .spacing(5),
+    )
+    .into()
+}
+
+/// Describes how the value of holdings changed since the stored prices of a day ago.
+fn format_change(
+    dashboard: &Dashboard,
+    holdings: &Holdings,
+    prices: Option<&Prices>,
+    prices_before: Option<&Prices>,
+) -> String {
+    let (Some(prices), Some(prices_before)) = (prices, prices_before) else {
+        return "not enough price history yet".to_string();
+    };
+    match holdings.change(prices, prices_before) {
+        Some((change, ratio)) => format!(
+            "{}{} ({:+.2}%)",
+            if change < 0.0 { "-" } else { "+" },
+            dashboard.currency.format(change.abs()),
+            ratio * 100.0
+        ),
+        None => "--".to_string(),
+    }
+}
+
+/// Describes an amount of ORE with its value at the price of a past moment, when stored.
+fn format_amount_at(dashboard: &Dashboard, amount: f64, timestamp: i64) -> String {
+    let ore = format!("{} ORE", round_dp(amount, BALANCE_PRECISION));
+    match dashboard.value_at(amount, timestamp) {
+        Some(value) => format!("{} ({})", ore, dashboard.currency.format(value)),
+        None => ore,
+    }
+}
+
+pub fn price_chart_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let to = chrono::Utc::now().timestamp();
+    let from = to - dashboard.price_range.seconds();
+    let points = dashboard
+        .price_history
+        .iter()
+        .filter(|p| p.timestamp >= from)
+        .count();
+
+    container(
+        column![
+            row![
+                text("Price").size(24),
+                horizontal_space(),
+                pick_list(
+                    PRICE_RANGES,
+                    Some(dashboard.price_range),
+                    Message::PriceRangeSelected
+                ),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            canvas(Chart::price(
+                &dashboard.price_history,
+                dashboard.currency.code(),
+                from,
+                to
+            ))
+            .width(Length::Fill)
+            .height(CHART_HEIGHT),
+            text(format!(
+                "{} prices in the last {}",
+                points, dashboard.price_range
+            ))
+            .size(SUBHEAD_TEXT),
+            button(text("Close")).on_press(Message::HideModal(None)),
+        ]
+        .spacing(10),
+    )
+    .width(650)
+    .max_height(560)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+fn format_rule(rule: &AutomationRule) -> String {
+    let target = match &rule.wallet_address {
+        Some(wallet) => format!(" to {}", abbreviate(wallet)),
+        None => String::default(),
+    };
+    let cap = match rule.daily_cap {
+        Some(cap) => format!(", up to {} ORE a day", cap),
+        None => String::default(),
+    };
+    format!(
+        "{} above {} ORE{}, every {} at most{}",
+        rule.action,
+        rule.threshold,
+        target,
+        format_duration(rule.min_interval_seconds),
+        cap
+    )
+}
+
+pub fn account_detail_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let Some(account) = dashboard.current_id.and_then(|id| dashboard.account(id)) else {
+        return text("No account selected").into();
+    };
+    let to = chrono::Utc::now().timestamp();
+    let from = to - dashboard.chart_range.seconds();
+    let snapshots = &dashboard.snapshots;
+    let hashes: u64 = hashes_per_hour(snapshots).iter().map(|(_, h)| h).sum();
+    let (rewards, rewards_value) = dashboard.rewards_value(snapshots);
+    let mut uptime = row![text("Uptime:")].spacing(10);
+    for (range, u) in UPTIME_RANGES.iter().zip(&dashboard.uptime) {
+        uptime = uptime.push(text(match u.percent() {
+            Some(percent) => format!("{}: {:.2}%", range, percent),
+            None => format!("{}: --", range),
+        }));
+    }
+    let timeline = &dashboard.timeline;
+
+    container(
+        column![
+            row![
+                text(abbreviate(&account.status.authority.to_string())).size(24),
+                horizontal_space(),
+                pick_list(
+                    ChartRange::ALL,
+                    Some(dashboard.chart_range),
+                    Message::ChartRangeSelected
+                ),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+            scrollable(
+                column![
+                    canvas(Chart::stake(snapshots, from, to))
+                        .width(Length::Fill)
+                        .height(CHART_HEIGHT),
+                    canvas(Chart::rewards(snapshots, from, to))
+                        .width(Length::Fill)
+                        .height(CHART_HEIGHT),
+                    canvas(Chart::hash_rate(snapshots, from, to))
+                        .width(Length::Fill)
+                        .height(CHART_HEIGHT),
+                    uptime,
+                    text(format!(
+                        "Outages in the last {}: {}, longest {}",
+                        dashboard.chart_range,
+                        timeline.outages.len(),
+                        format_duration(timeline.longest_outage())
+                    ))
+                    .size(SUBHEAD_TEXT),
+                    canvas(Timeline::new(timeline.clone()))
+                        .width(Length::Fill)
+                        .height(TIMELINE_HEIGHT),
+                ]
+                .spacing(10)
+            )
+            .height(Length::Shrink),
+            text(format!(
+                "{} snapshots, {} hashes in the last {}",
+                snapshots.len(),
+                hashes,
+                dashboard.chart_range
+            ))
+            .size(SUBHEAD_TEXT),
+            text(format!(
+                "Rewards in the last {}: {} ORE, worth {} at the prices of the time",
+                dashboard.chart_range,
+                round_dp(rewards, BALANCE_PRECISION),
+                dashboard.currency.format(rewards_value)
+            ))
+            .size(SUBHEAD_TEXT),
+            row![
+                text("Commitment").size(SUBHEAD_TEXT),
+                commitment_row(
+                    Some(account.id),
+                    account.miner.read_commitment,
+                    account.miner.confirm_commitment
+                ),
+                horizontal_space(),
+                button(text("Close")).on_press(Message::HideModal(None)),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        ]
+        .spacing(10),
+    )
+    .width(650)
+    .max_height(560)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+/// Shows the earnings of the fleet per day, with the rate per hour below.
+fn fleet_earnings_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let Some(e) = dashboard.earnings else {
+        return column![text("--"), text("--/h").size(SUBHEAD_TEXT)]
+            .align_x(iced::Alignment::End)
+            .into();
+    };
+    column![
+        text(format!(
+            "{}/d ({})",
+            round_dp(e.per_day(), BALANCE_PRECISION),
+            dashboard.format_value(e.per_day())
+        )),
+        text(format!(
+            "{}/h ({})",
+            round_dp(e.per_hour, BALANCE_PRECISION),
+            dashboard.format_value(e.per_hour)
+        ))
+        .size(SUBHEAD_TEXT)
+    ]
+    .align_x(iced::Alignment::End)
+    .into()
+}
+
+/// The currencies offered for selection, including one set only in the config.
+fn currency_options(current: &Currency) -> Vec<Currency> {
+    let mut options = Currency::common();
+    if !options.contains(current) {
+        options.push(current.clone());
+    }
+    options
+}
+
+/// Shows the price with where and when it was last quoted, flagging a price
+/// kept from an earlier quote because the latest fetch failed.
+fn price_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let price = match &dashboard.price_quote {
+        Some(quote) => quote.currency.format(quote.price),
+        None => "--".to_string(),
+    };
+    let label = match (&dashboard.price_quote, &dashboard.price_error) {
+        (Some(quote), Some(_)) => {
+            text(format!("Stale since {}", get_time_of_day(quote.timestamp))).style(text::danger)
+        }
+        (None, Some(e)) => text(e.clone()).style(text::danger),
+        (Some(quote), None) if quote.stale_rate => text(format!(
+            "{} {}, last known {} rate",
+            get_time_of_day(quote.timestamp),
+            quote,
+            quote.currency
+        ))
+        .style(text::danger),
+        (Some(quote), None) => text(format!("{} {}", get_time_of_day(quote.timestamp), quote)),
+        (None, None) => text("Not updated yet"),
+    };
+    column![text(price), label.size(SUBHEAD_TEXT)]
+        .align_x(iced::Alignment::End)
+        .into()
+}
+
 pub fn active_num_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
     let active_num = dashboard.active_num;
     let all_num = dashboard.accounts.len();
@@ -468,6 +1472,116 @@ pub fn active_num_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
         .into()
 }
 
+/// Offers the backups of a config that couldn't be loaded, instead of starting empty.
+pub fn restore_config_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let mut backups = column![].spacing(5);
+    for (path, modified) in config::backups(&dashboard.config_path) {
+        let written = chrono::DateTime::<chrono::Local>::from(modified);
+        backups = backups.push(
+            row![
+                text(format!(
+                    "{} written {}",
+                    path.file_name().unwrap_or_default().to_string_lossy(),
+                    written.format("%Y-%m-%d %H:%M:%S")
+                ))
+                .width(Length::Fill),
+                button(text("Restore")).on_press(Message::RestoreConfig(path)),
+            ]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+        );
+    }
+
+    container(
+        column![
+            text("Restore config").size(24),
+            text(format!(
+                "{} couldn't be loaded: {}",
+                dashboard.config_path.display(),
+                dashboard.config_error.as_deref().unwrap_or_default()
+            ))
+            .style(text::danger),
+            scrollable(backups).height(Length::Shrink),
+            button(text("Start empty"))
+                .on_press(Message::HideModal(None))
+                .style(button::secondary),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(500)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
+pub fn settings_view<'a>(dashboard: &'a Dashboard) -> Element<'a, Message> {
+    let form = &dashboard.settings_form;
+    let field = |label: &'a str, value: &'a str, setting: SettingField| {
+        column![
+            text(label),
+            text_input("", value).on_input(move |value| Message::SettingsInput(setting, value)),
+        ]
+        .spacing(5)
+    };
+
+    container(
+        column![
+            text("Settings").size(24),
+            checkbox("Auto Refresh", dashboard.auto_refresh).on_toggle(Message::ToggleSubscription),
+            row![
+                text("Fetch Mode:"),
+                pick_list(
+                    FetchMode::ALL,
+                    Some(dashboard.fetch_mode),
+                    Message::FetchModeSelected
+                )
+            ]
+            .spacing(5)
+            .align_y(iced::Alignment::Center),
+            field(
+                "Refresh interval (seconds)",
+                &form.data_interval,
+                SettingField::DataInterval
+            ),
+            field(
+                "Save interval (seconds)",
+                &form.save_interval,
+                SettingField::SaveInterval
+            ),
+            field(
+                "Online threshold (seconds since the last hash)",
+                &form.active_period,
+                SettingField::ActivePeriod
+            ),
+            text("Defaults of new accounts").size(SUBHEAD_TEXT),
+            cluster_picker(&form.json_rpc_url, |url| {
+                Message::SettingsInput(SettingField::JsonRpcUrl, url)
+            }),
+            field("Json rpc url", &form.json_rpc_url, SettingField::JsonRpcUrl),
+            field(
+                "Priority fee",
+                &form.priority_fee,
+                SettingField::PriorityFee
+            ),
+            text(form.error.as_deref().unwrap_or_default()).style(text::danger),
+            row![
+                button(text("Apply")).on_press(Message::ApplySettings),
+                button(text("Close"))
+                    .on_press(Message::HideModal(None))
+                    .style(button::secondary),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10),
+    )
+    .width(550)
+    .max_height(600)
+    .padding(10)
+    .style(container::rounded_box)
+    .into()
+}
+
 pub fn dialog_view<'a>(dashboard: &Dashboard) -> Element<'a, Message> {
     let (text_color, command): (Box<dyn Fn(&Theme) -> text::Style>, Option<Box<Message>>) =
         match dashboard.dialog.content_type {